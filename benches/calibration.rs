@@ -0,0 +1,152 @@
+//! Day 1 part 2 on a large generated input, read into memory first (as `aoc23 1:2` does) and streamed (as
+//! `aoc23 stream 1:2` does): `cargo bench --bench calibration`. Set `AOC23_BENCH_MB` to change the input size.
+//! A third row runs the original per-line implementation, from before the digit-word automaton, on the same input.
+//!
+//! The streamed run goes first, because the process's peak memory only ever goes up.
+
+use std::io::{self, Read};
+use std::time::{Duration, Instant};
+use aoc23::days::day1::{self, Vocabulary};
+use aoc23::record::peak_memory;
+
+const LINES: [&str; 7] = [
+    "two1nine",
+    "eightwothree",
+    "abcone2threexyz",
+    "xtwone3four",
+    "4nineeightseven2",
+    "zoneight234",
+    "7pqrstsixteen",
+];
+
+// the example's lines over and over, until there are at least `size` bytes, without ever holding more than a line
+struct Generated {
+    size: usize,
+    written: usize,
+    line: usize,
+    pending: Vec<u8>,
+}
+
+impl Generated {
+    fn new(size: usize) -> Self {
+        Generated { size, written: 0, line: 0, pending: vec![] }
+    }
+}
+
+impl Read for Generated {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            if self.pending.is_empty() {
+                if self.written >= self.size {
+                    break;
+                }
+                self.pending.extend_from_slice(LINES[self.line % LINES.len()].as_bytes());
+                self.pending.push(b'\n');
+                self.line += 1;
+            }
+            let n = (buf.len() - filled).min(self.pending.len());
+            buf[filled..filled + n].copy_from_slice(&self.pending[..n]);
+            self.pending.drain(..n);
+            self.written += n;
+            filled += n;
+        }
+        Ok(filled)
+    }
+}
+
+// day 1 part 2 as it was first written: each line is copied into a `Vec<char>`, forwards and backwards, and every
+// digit word found in a five character window is replaced with its digit
+fn original_calibration_sum(input: &str) -> u64 {
+    input.lines()
+        .map(|l| {
+            let mut c = l.chars().collect::<Vec<char>>();
+            let mut c_rev = c.iter().cloned().rev().collect::<Vec<char>>();
+            c.push(' ');
+            c.push(' ');
+            c_rev.push(' ');
+            c_rev.push(' ');
+            let mut s = c.iter().cloned().collect::<String>();
+            let mut s_rev = c_rev.iter().cloned().collect::<String>();
+
+            if c.len() >= 5 {
+                for i in 0..c.len() - 5 {
+                    s = replace(&c[i..i + 5], &s);
+                }
+                for i in 0..c_rev.len() - 5 {
+                    s_rev = replace_rev(&c_rev[i..i + 5], &s_rev);
+                }
+            }
+
+            let mut out = String::with_capacity(2);
+            out.push(*s.chars().filter(|c| c.is_numeric()).collect::<Vec<char>>().first().unwrap());
+            out.push(*s_rev.chars().filter(|c| c.is_numeric()).collect::<Vec<char>>().first().unwrap());
+            out.parse::<u64>().unwrap()
+        }).sum()
+}
+
+fn replace(slice: &[char], s: &str) -> String {
+    match slice {
+        ['z', 'e', 'r', 'o', _] => s.replacen("zero", "0", 1),
+        ['o', 'n', 'e', _, _] => s.replacen("one", "1", 1),
+        ['t', 'w', 'o', _, _] => s.replacen("two", "2", 1),
+        ['t', 'h', 'r', 'e', 'e'] => s.replacen("three", "3", 1),
+        ['f', 'o', 'u', 'r', _] => s.replacen("four", "4", 1),
+        ['f', 'i', 'v', 'e', _] => s.replacen("five", "5", 1),
+        ['s', 'i', 'x', _, _] => s.replacen("six", "6", 1),
+        ['s', 'e', 'v', 'e', 'n'] => s.replacen("seven", "7", 1),
+        ['e', 'i', 'g', 'h', 't'] => s.replacen("eight", "8", 1),
+        ['n', 'i', 'n', 'e', _] => s.replacen("nine", "9", 1),
+        _ => s.to_string(),
+    }
+}
+
+fn replace_rev(slice: &[char], s: &str) -> String {
+    match slice {
+        ['o', 'r', 'e', 'z', _] => s.replacen("orez", "0", 1),
+        ['e', 'n', 'o', _, _] => s.replacen("eno", "1", 1),
+        ['o', 'w', 't', _, _] => s.replacen("owt", "2", 1),
+        ['e', 'e', 'r', 'h', 't'] => s.replacen("eerht", "3", 1),
+        ['r', 'u', 'o', 'f', _] => s.replacen("ruof", "4", 1),
+        ['e', 'v', 'i', 'f', _] => s.replacen("evif", "5", 1),
+        ['x', 'i', 's', _, _] => s.replacen("xis", "6", 1),
+        ['n', 'e', 'v', 'e', 's'] => s.replacen("neves", "7", 1),
+        ['t', 'h', 'g', 'i', 'e'] => s.replacen("thgie", "8", 1),
+        ['e', 'n', 'i', 'n', _] => s.replacen("enin", "9", 1),
+        _ => s.to_string(),
+    }
+}
+
+fn mib(bytes: u64) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+fn report(name: &str, size: usize, elapsed: Duration, sum: u64) {
+    let peak = peak_memory().map_or("unknown".to_string(), |bytes| format!("{:.1} MiB", mib(bytes)));
+    println!(
+        "{name:<10} {:>8.1} ms  {:>8.1} MiB/s  peak memory {peak:>10}  (sum {sum})",
+        elapsed.as_secs_f64() * 1000.0,
+        mib(size as u64) / elapsed.as_secs_f64(),
+    );
+}
+
+fn main() {
+    let mb = std::env::var("AOC23_BENCH_MB").ok().and_then(|s| s.parse::<usize>().ok()).unwrap_or(256);
+    let size = mb * 1024 * 1024;
+    println!("day 1 part 2 on {mb} MiB");
+
+    let start = Instant::now();
+    let sum = day1::calibration_sum_streaming(Generated::new(size), &Vocabulary::english()).unwrap();
+    report("streamed", size, start.elapsed(), sum);
+
+    let start = Instant::now();
+    let mut input = String::new();
+    Generated::new(size).read_to_string(&mut input).unwrap();
+    let sum = day1::calibration_sum(&input, &Vocabulary::english()).unwrap();
+    report("in memory", size, start.elapsed(), sum);
+
+    let start = Instant::now();
+    let original = original_calibration_sum(&input);
+    report("original", size, start.elapsed(), original);
+    assert_eq!(original, sum, "the original implementation disagrees with the automaton");
+}
@@ -0,0 +1,159 @@
+//! Running the whole calendar at once.
+
+use std::num::NonZeroUsize;
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::bench::{format_ns, OutputFormat};
+use crate::inputs::{self, InputKind};
+use crate::record::Record;
+use crate::registry::Day;
+use crate::runner::{run_catching, run_with_timeout, Failure};
+use crate::solution::Solution;
+
+/// Settings for [`run_all`].
+#[derive(Copy, Clone, Debug)]
+pub struct AllOptions {
+    /// Which local inputs to run on.
+    pub kind: InputKind,
+    /// How many worker threads to use.
+    pub jobs: usize,
+    /// Whether to print each part's diagnostics after the answers.
+    pub diagnostics: bool,
+    /// How long each part gets before it's stopped, if there's a limit.
+    pub timeout: Option<Duration>,
+    /// Whether to print a table or a JSON [`Record`] per part.
+    pub format: OutputFormat,
+}
+
+impl Default for AllOptions {
+    fn default() -> Self {
+        AllOptions {
+            kind: InputKind::Real,
+            jobs: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            diagnostics: false,
+            timeout: None,
+            format: OutputFormat::Table,
+        }
+    }
+}
+
+enum Outcome {
+    Solved(Solution),
+    Failed(Failure),
+    NoInput,
+}
+
+struct PartResult {
+    year: i32,
+    day: usize,
+    part: usize,
+    elapsed: Duration,
+    outcome: Outcome,
+}
+
+fn run_one(puzzle: &Day, part: usize, options: &AllOptions) -> PartResult {
+    let (year, day) = (puzzle.year, puzzle.day);
+    let Some(input) = inputs::read_local(year, day, part, options.kind) else {
+        return PartResult { year, day, part, elapsed: Duration::ZERO, outcome: Outcome::NoInput };
+    };
+
+    let start = Instant::now();
+    let result = match options.timeout {
+        Some(timeout) => run_with_timeout(puzzle.part(part), input, timeout),
+        None => run_catching(puzzle.part(part), input),
+    };
+    let outcome = match result {
+        Ok(solution) => Outcome::Solved(solution),
+        Err(failure) => Outcome::Failed(failure),
+    };
+    PartResult { year, day, part, elapsed: start.elapsed(), outcome }
+}
+
+/// Runs every part of `days` on a pool of `options.jobs` worker threads and prints the results in calendar
+/// order once they're all done; returns false if any part failed.
+pub fn run_all(days: &[&Day], options: &AllOptions) -> bool {
+    let parts = days.iter().flat_map(|&d| [(d, 1), (d, 2)]).collect::<Vec<_>>();
+
+    // failures are reported in the results, so keep the default hook from interleaving them with the output
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    let start = Instant::now();
+    let next = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|s| {
+        for _ in 0..options.jobs.clamp(1, parts.len()) {
+            let tx = tx.clone();
+            let (next, parts) = (&next, &parts);
+            s.spawn(move || {
+                while let Some(&(day, part)) = parts.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let result = run_one(day, part, options);
+                    tx.send(result).unwrap();
+                }
+            });
+        }
+    });
+    drop(tx);
+    let total = start.elapsed();
+
+    panic::set_hook(hook);
+
+    let mut results = rx.into_iter().collect::<Vec<_>>();
+    results.sort_by_key(|r| (r.year, r.day, r.part));
+    let all_succeeded = results.iter().all(|r| !matches!(r.outcome, Outcome::Failed(_)));
+
+    if options.format == OutputFormat::Json {
+        for r in results {
+            let key = (r.year, r.day, r.part);
+            let record = match r.outcome {
+                Outcome::Solved(solution) => Record::new(key, options.kind.to_string(), &Ok(solution), r.elapsed),
+                Outcome::Failed(failure) => Record::new(key, options.kind.to_string(), &Err(failure), r.elapsed),
+                Outcome::NoInput => Record::no_input(key, options.kind.to_string(), no_input(&r, options)),
+            };
+            println!("{}", record.to_json());
+        }
+        return all_succeeded;
+    }
+
+    println!("{:>3}  {:>4}  {:>12}  answer", "day", "part", "time");
+    for r in &results {
+        let answer = match &r.outcome {
+            Outcome::Solved(solution) => solution.answer.to_string(),
+            Outcome::Failed(failure) => format!("error: {failure}"),
+            Outcome::NoInput => no_input(r, options),
+        };
+        let time = match r.outcome {
+            Outcome::NoInput => "-".to_string(),
+            _ => format_ns(r.elapsed.as_nanos() as u64),
+        };
+        println!("{:>3}  {:>4}  {:>12}  {answer}", r.day, r.part, time);
+    }
+
+    if options.diagnostics {
+        for r in &results {
+            if let Outcome::Solved(Solution { diagnostics: Some(diagnostics), .. }) = &r.outcome {
+                println!();
+                println!("day {} part {}:", r.day, r.part);
+                println!("{diagnostics}");
+            }
+        }
+    }
+
+    let cpu_time = results.iter().map(|r| r.elapsed).sum::<Duration>();
+    println!();
+    println!(
+        "total: {} wall time, {} summed over parts, {} threads",
+        format_ns(total.as_nanos() as u64),
+        format_ns(cpu_time.as_nanos() as u64),
+        options.jobs.clamp(1, parts.len()),
+    );
+
+    all_succeeded
+}
+
+fn no_input(r: &PartResult, options: &AllOptions) -> String {
+    format!("no input at {}", inputs::local_path(r.year, r.day, r.part, options.kind).display())
+}
@@ -0,0 +1,99 @@
+//! Recorded answers to check solutions against.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use crate::inputs::InputKind;
+
+/// The default answers file for your own answers, relative to the working directory. It isn't checked in.
+pub const ANSWERS_FILE: &str = "answers.txt";
+
+/// The checked-in answers to the examples in the puzzle descriptions, relative to the working directory.
+pub const EXAMPLE_ANSWERS_FILE: &str = "example-answers.txt";
+
+/// Known answers, parsed from a file with one answer per line in the form
+/// `<year> <day>:<part> <real|example> <answer>`. Blank lines and lines starting with `#` are ignored.
+#[derive(Debug, Default)]
+pub struct Answers(HashMap<(i32, usize, usize, InputKind), String>);
+
+impl Answers {
+    /// Loads answers from a file.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let s = fs::read_to_string(path)?;
+        Self::from_str(&s).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Adds the answers from `other`, which win where both have one for the same part.
+    pub fn extend(&mut self, other: Answers) {
+        self.0.extend(other.0);
+    }
+
+    /// The recorded answer for a part, if there is one.
+    pub fn get(&self, year: i32, day: usize, part: usize, kind: InputKind) -> Option<&str> {
+        self.0.get(&(year, day, part, kind)).map(String::as_str)
+    }
+}
+
+impl FromStr for Answers {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut answers = HashMap::new();
+        for (n, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = || {
+                format!("line {}: expected `<year> <day>:<part> <real|example> <answer>`, got `{line}`", n + 1)
+            };
+            let mut fields = line.splitn(4, ' ');
+            let year = fields.next().and_then(|s| i32::from_str(s).ok()).ok_or_else(invalid)?;
+            let (day, part) = fields.next().and_then(|s| s.split_once(':')).ok_or_else(invalid)?;
+            let day = usize::from_str(day).map_err(|_| invalid())?;
+            let part = usize::from_str(part).map_err(|_| invalid())?;
+            let kind = fields.next().and_then(|s| InputKind::from_str(s).ok()).ok_or_else(invalid)?;
+            let answer = fields.next().map(str::trim).filter(|s| !s.is_empty()).ok_or_else(invalid)?;
+
+            answers.insert((year, day, part, kind), answer.to_string());
+        }
+
+        Ok(Answers(answers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_are_kept_apart_by_year() {
+        let answers = Answers::from_str("\
+# <year> <day>:<part> <real|example> <answer>
+2023 1:1 example 142
+2022 1:1 example 24000
+
+2022 1:2 real 45000
+").unwrap();
+        assert_eq!(answers.get(2023, 1, 1, InputKind::Example), Some("142"));
+        assert_eq!(answers.get(2022, 1, 1, InputKind::Example), Some("24000"));
+        assert_eq!(answers.get(2022, 1, 2, InputKind::Real), Some("45000"));
+        assert_eq!(answers.get(2023, 1, 2, InputKind::Real), None);
+    }
+
+    #[test]
+    fn example_answers_parse() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(EXAMPLE_ANSWERS_FILE);
+        let answers = Answers::load(&path).unwrap();
+        assert_eq!(answers.get(2023, 1, 2, InputKind::Example), Some("281"));
+    }
+
+    #[test]
+    fn answers_without_a_year_are_rejected() {
+        let error = Answers::from_str("1:1 example 142\n").unwrap_err();
+        assert_eq!(error, "line 1: expected `<year> <day>:<part> <real|example> <answer>`, got `1:1 example 142`");
+    }
+}
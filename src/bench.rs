@@ -0,0 +1,177 @@
+//! Timing parts over repeated runs.
+
+use std::panic;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use crate::inputs::{self, InputKind};
+use crate::registry::Day;
+use crate::runner::run_catching;
+
+/// How to print results (of benchmarks, or of running everything).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum OutputFormat {
+    /// A human-readable table.
+    Table,
+    /// JSON, for keeping a history or feeding a dashboard.
+    Json,
+}
+
+/// Settings for [`bench()`].
+#[derive(Copy, Clone, Debug)]
+pub struct BenchOptions {
+    /// Untimed runs before timing starts.
+    pub warmup: usize,
+    /// Timed runs.
+    pub runs: usize,
+    /// Which local inputs to run on.
+    pub kind: InputKind,
+    /// How to print the results.
+    pub format: OutputFormat,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        BenchOptions {
+            warmup: 3,
+            runs: 10,
+            kind: InputKind::Real,
+            format: OutputFormat::Table,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Stats {
+    min_ns: u64,
+    median_ns: u64,
+    mean_ns: u64,
+    max_ns: u64,
+}
+
+impl Stats {
+    fn from_samples(mut samples: Vec<Duration>) -> Self {
+        samples.sort();
+        let ns = |d: Duration| d.as_nanos() as u64;
+        let n = samples.len();
+        let median = if n % 2 == 0 {
+            (samples[n / 2 - 1] + samples[n / 2]) / 2
+        } else {
+            samples[n / 2]
+        };
+
+        Stats {
+            min_ns: ns(samples[0]),
+            median_ns: ns(median),
+            mean_ns: ns(samples.iter().sum::<Duration>() / n as u32),
+            max_ns: ns(samples[n - 1]),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct BenchResult {
+    year: i32,
+    day: usize,
+    part: usize,
+    #[serde(flatten)]
+    stats: Option<Stats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct BenchReport {
+    input: String,
+    warmup: usize,
+    runs: usize,
+    results: Vec<BenchResult>,
+}
+
+fn bench_part(puzzle: &Day, part: usize, options: &BenchOptions) -> BenchResult {
+    let (year, day, solver) = (puzzle.year, puzzle.day, puzzle.part(part));
+    let mut result = BenchResult { year, day, part, stats: None, error: None };
+    let Some(input) = inputs::read_local(year, day, part, options.kind) else {
+        result.error = Some(format!("no input at {}", inputs::local_path(year, day, part, options.kind).display()));
+        return result;
+    };
+
+    for _ in 0..options.warmup {
+        if let Err(failure) = run_catching(solver, input.clone()) {
+            result.error = Some(failure.to_string());
+            return result;
+        }
+    }
+
+    let mut samples = Vec::with_capacity(options.runs);
+    for _ in 0..options.runs {
+        let input = input.clone();
+        let start = Instant::now();
+        let outcome = run_catching(solver, input);
+        samples.push(start.elapsed());
+        if let Err(failure) = outcome {
+            result.error = Some(failure.to_string());
+            return result;
+        }
+    }
+
+    result.stats = Some(Stats::from_samples(samples));
+    result
+}
+
+/// Formats a duration in nanoseconds with a sensible unit.
+pub fn format_ns(ns: u64) -> String {
+    let ns = ns as f64;
+    if ns >= 1e9 {
+        format!("{:.3} s", ns / 1e9)
+    } else if ns >= 1e6 {
+        format!("{:.3} ms", ns / 1e6)
+    } else if ns >= 1e3 {
+        format!("{:.3} µs", ns / 1e3)
+    } else {
+        format!("{ns} ns")
+    }
+}
+
+fn print_table(report: &BenchReport) {
+    println!("{} input, {} warmup runs, {} timed runs", report.input, report.warmup, report.runs);
+    println!("{:>3}  {:>4}  {:>12}  {:>12}  {:>12}  {:>12}", "day", "part", "min", "median", "mean", "max");
+    for r in &report.results {
+        match (&r.stats, &r.error) {
+            (Some(s), _) => println!(
+                "{:>3}  {:>4}  {:>12}  {:>12}  {:>12}  {:>12}",
+                r.day, r.part, format_ns(s.min_ns), format_ns(s.median_ns), format_ns(s.mean_ns), format_ns(s.max_ns),
+            ),
+            (None, Some(e)) => println!("{:>3}  {:>4}  {e}", r.day, r.part),
+            (None, None) => unreachable!(),
+        }
+    }
+}
+
+/// Benchmarks each of `parts` ((day, part) pairs, with 1-based parts) and prints the results; returns false if any
+/// of them failed.
+pub fn bench(parts: &[(&Day, usize)], options: &BenchOptions) -> bool {
+    // errors are reported in the results, so keep the default hook from interleaving them with the output
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    let results = parts
+        .iter()
+        .map(|&(day, part)| bench_part(day, part, options))
+        .collect::<Vec<_>>();
+
+    panic::set_hook(hook);
+
+    let report = BenchReport {
+        input: options.kind.to_string(),
+        warmup: options.warmup,
+        runs: options.runs,
+        results,
+    };
+
+    match options.format {
+        OutputFormat::Table => print_table(&report),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report).unwrap()),
+    }
+
+    report.results.iter().all(|r| r.error.is_none())
+}
@@ -0,0 +1,101 @@
+//! Stopping long-running solvers cooperatively.
+//!
+//! The runner gives the thread a solver runs on a [`CancellationToken`], and cancels it when the solver runs out of
+//! time. Solvers (and the shared algorithms they're built on) call [`checkpoint`] or [`checkpoint_with`] in their
+//! long loops; once the token is cancelled the next checkpoint unwinds out of the solver with a [`Cancelled`]
+//! payload, which [`run_catching`](crate::runner::run_catching) reports as a timeout rather than a crash. Outside
+//! the runner, or without a timeout, checkpoints do nothing.
+
+use std::cell::RefCell;
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A flag shared between the runner and the solver it's running.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// A token that hasn't been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks the solver to stop at its next checkpoint.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`CancellationToken::cancel`] has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// What a cancelled solver unwinds with.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Cancelled {
+    /// How far the solver got, if the checkpoint said.
+    pub progress: Option<String>,
+}
+
+thread_local! {
+    static TOKEN: RefCell<Option<CancellationToken>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` with `token` as the current thread's token, so the checkpoints inside it can be cancelled.
+pub fn with_token<T>(token: CancellationToken, f: impl FnOnce() -> T) -> T {
+    let previous = TOKEN.with(|t| t.replace(Some(token)));
+    // put the previous token back even if `f` unwinds, which is how cancelling it ends
+    struct Restore(Option<CancellationToken>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            TOKEN.with(|t| *t.borrow_mut() = self.0.take());
+        }
+    }
+    let _restore = Restore(previous);
+    f()
+}
+
+/// Whether the current thread's token has been cancelled.
+pub fn is_cancelled() -> bool {
+    TOKEN.with(|t| t.borrow().as_ref().is_some_and(CancellationToken::is_cancelled))
+}
+
+/// Stops the solver if it's been cancelled.
+pub fn checkpoint() {
+    if is_cancelled() {
+        panic::resume_unwind(Box::new(Cancelled::default()));
+    }
+}
+
+/// Stops the solver if it's been cancelled, reporting how far it got with the message `progress` makes (which is
+/// only called when it's needed).
+pub fn checkpoint_with(progress: impl FnOnce() -> String) {
+    if is_cancelled() {
+        panic::resume_unwind(Box::new(Cancelled { progress: Some(progress()) }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkpoints_only_stop_once_cancelled() {
+        let token = CancellationToken::new();
+        let result = panic::catch_unwind(|| {
+            with_token(token.clone(), || {
+                checkpoint();
+                token.cancel();
+                checkpoint_with(|| "halfway".to_string());
+            })
+        });
+        let payload = result.unwrap_err().downcast::<Cancelled>().unwrap();
+        assert_eq!(payload.progress.as_deref(), Some("halfway"));
+
+        // the token is only in force inside `with_token`
+        checkpoint();
+        assert!(!is_cancelled());
+    }
+}
@@ -1,75 +1,493 @@
-use std::str::FromStr;
-
-pub(crate) fn part1(input: String) {
-    println!("{}", input.lines()
-        .map(|l| {
-            let c = l.chars().filter(|c| c.is_numeric())
-                .collect::<Vec<char>>();
-            let mut s = String::with_capacity(2);
-            s.push(*c.first().unwrap());
-            s.push(*c.last().unwrap());
-            i32::from_str(&s).unwrap()
-        }).sum::<i32>());
-}
-
-pub(crate) fn part2(input: String) {
-    println!("{}", input.lines()
-        .map(|l| {
-            let mut c = l.chars().collect::<Vec<char>>();
-            let mut c_rev = c.iter().cloned().rev().collect::<Vec<char>>();
-            c.push(' ');
-            c.push(' ');
-            c_rev.push(' ');
-            c_rev.push(' ');
-            let mut s = c.iter().cloned().collect::<String>();
-            let mut s_rev = c_rev.iter().cloned().collect::<String>();
-
-            if c.len() >= 5 {
-                for i in 0..c.len() - 5 {
-                    let slice = &c[i..i + 5];
-                    s = replace(slice, &s);
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::io::{self, Read};
+use crate::parse::{self, Line, ParseError};
+use crate::solution::{Solution, SolveResult};
+
+crate::registry::puzzle!(2023, 1, "Trebuchet?!", part1, part2, |input| parse::line_errors(input, |input| {
+    // anything goes but letters and digits, as long as there's something
+    for line in parse::lines(DAY, parse::non_empty(DAY, input)?) {
+        if let Some((x, c)) = line.text.chars().enumerate().find(|(_, c)| !c.is_ascii_alphanumeric()) {
+            return Err(line.error_at(x, &c.to_string(), "unexpected character"));
+        }
+    }
+    Ok(())
+}));
+
+/// The spelled-out digits, `zero` to `nine`.
+pub const ENGLISH: [&str; 10] = ["zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+
+/// `ten` to `nineteen`, for [`Vocabulary::with_teens`].
+pub const ENGLISH_TEENS: [&str; 10] =
+    ["ten", "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen"];
+
+/// The digits in German.
+pub const GERMAN: [&str; 10] = ["null", "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun"];
+
+/// The digits in French.
+pub const FRENCH: [&str; 10] = ["zéro", "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf"];
+
+/// The words that count as numbers on a calibration line, with what they're worth. A word worth more than 9 stands
+/// for all its digits, so `twelve` starts with a 1 and ends with a 2.
+#[derive(Clone, Debug)]
+pub struct Vocabulary {
+    words: Vec<(String, u32)>,
+}
+
+impl Vocabulary {
+    /// Just `0` to `9`, as in part 1.
+    pub fn digits() -> Self {
+        Vocabulary { words: (0..10).map(|n| (n.to_string(), n)).collect() }
+    }
+
+    /// The digits, plus each of `names` (`zero` to `nine` in some language) standing for its position.
+    pub fn spelled(names: &[&str; 10]) -> Self {
+        Self::digits().with(names.iter().zip(0..))
+    }
+
+    /// The digits and [`ENGLISH`], as in part 2.
+    pub fn english() -> Self {
+        Self::spelled(&ENGLISH)
+    }
+
+    /// Adds [`ENGLISH_TEENS`].
+    pub fn with_teens(self) -> Self {
+        self.with(ENGLISH_TEENS.iter().zip(10..))
+    }
+
+    /// Adds more words.
+    pub fn with<S: ToString>(mut self, words: impl IntoIterator<Item = (S, u32)>) -> Self {
+        self.words.extend(words.into_iter().map(|(word, value)| (word.to_string(), value)));
+        self
+    }
+}
+
+/// A word from a [`Vocabulary`] found on a line, at bytes `start..end`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Token {
+    /// The byte offset the word starts at.
+    pub start: usize,
+    /// The byte offset just after the word.
+    pub end: usize,
+    /// What it's worth.
+    pub value: u32,
+}
+
+impl Token {
+    /// The first digit of the value.
+    pub fn first_digit(&self) -> u32 {
+        let mut n = self.value;
+        while n >= 10 {
+            n /= 10;
+        }
+        n
+    }
+
+    /// The last digit of the value.
+    pub fn last_digit(&self) -> u32 {
+        self.value % 10
+    }
+}
+
+/// Finds every word of a [`Vocabulary`] on a line in one pass, overlapping ones included (`twone` is both `two` and
+/// `one`).
+///
+/// It's an Aho-Corasick automaton over bytes, with the failure links folded into a full transition table, so each
+/// byte costs one lookup.
+#[derive(Clone, Debug)]
+pub struct Matcher {
+    // next state for each state and byte; state 0 is the start
+    transitions: Vec<[u32; 256]>,
+    // the words (length and value) that end in each state, longest first
+    outputs: Vec<Vec<(usize, u32)>>,
+}
+
+impl Matcher {
+    /// A matcher for the words of `vocabulary`.
+    pub fn new(vocabulary: &Vocabulary) -> Self {
+        // the trie, where 0 means no edge (nothing leads back to the root)
+        let mut transitions = vec![[0u32; 256]];
+        let mut outputs = vec![vec![]];
+        for (word, value) in vocabulary.words.iter().filter(|(word, _)| !word.is_empty()) {
+            let mut state = 0;
+            for &b in word.as_bytes() {
+                if transitions[state][b as usize] == 0 {
+                    transitions.push([0; 256]);
+                    outputs.push(vec![]);
+                    transitions[state][b as usize] = (transitions.len() - 1) as u32;
                 }
-                for i in 0..c_rev.len() - 5 {
-                    let slice = &c_rev[i..i + 5];
-                    s_rev = replace_rev(slice, &s_rev);
+                state = transitions[state][b as usize] as usize;
+            }
+            outputs[state].push((word.len(), *value));
+        }
+
+        // breadth first, so each state's failure state is finished before it's needed; a missing edge goes wherever
+        // the failure state's edge goes, and a state also matches everything its failure state does
+        let mut fail = vec![0; transitions.len()];
+        let mut queue = transitions[0].iter().filter(|&&s| s != 0).map(|&s| s as usize).collect::<VecDeque<_>>();
+        while let Some(state) = queue.pop_front() {
+            let inherited = outputs[fail[state]].clone();
+            outputs[state].extend(inherited);
+            outputs[state].sort_by(|a, b| b.cmp(a));
+            outputs[state].dedup_by_key(|(len, _)| *len);
+            let fallback = transitions[fail[state]];
+            for (next, fallback) in transitions[state].iter_mut().zip(fallback) {
+                if *next == 0 {
+                    *next = fallback;
+                } else {
+                    fail[*next as usize] = fallback as usize;
+                    queue.push_back(*next as usize);
                 }
             }
+        }
+
+        Matcher { transitions, outputs }
+    }
+
+    /// Every word on `line`, in order of where they end (and longest first where they end together).
+    pub fn tokens<'a>(&'a self, line: &'a str) -> impl Iterator<Item = Token> + 'a {
+        line.bytes().enumerate().scan(0, |state, (i, b)| {
+            *state = self.transitions[*state][b as usize] as usize;
+            Some((i + 1, *state))
+        }).flat_map(|(end, state)| {
+            self.outputs[state].iter().map(move |&(len, value)| Token { start: end - len, end, value })
+        })
+    }
+}
+
+/// The first and last tokens on a line: the first is the one that starts first, and the last the one that starts
+/// last, taking the longer word where two start together (so `seventeen` beats `seven` either way).
+pub fn first_and_last(tokens: impl IntoIterator<Item = Token>) -> Option<(Token, Token)> {
+    tokens.into_iter().fold(None, |ends, t| Some(extend(ends, t)))
+}
+
+// the first and last tokens once `t` has been seen too
+fn extend(ends: Option<(Token, Token)>, t: Token) -> (Token, Token) {
+    let Some((first, last)) = ends else {
+        return (t, t);
+    };
+    (
+        if (t.start, Reverse(t.end)) < (first.start, Reverse(first.end)) { t } else { first },
+        if (t.start, t.end) > (last.start, last.end) { t } else { last },
+    )
+}
+
+// the first digit of the first token and the last digit of the last
+fn value((first, last): (Token, Token)) -> u32 {
+    first.first_digit() * 10 + last.last_digit()
+}
+
+/// A line's calibration value: the first digit of its first token and the last digit of its last.
+pub fn calibration_value(line: Line, matcher: &Matcher) -> Result<u32, ParseError> {
+    first_and_last(matcher.tokens(line.text))
+        .map(value)
+        .ok_or_else(|| line.error(line.text, "no digit on this line"))
+}
+
+/// The sum of every line's calibration value, reading numbers with `vocabulary`.
+pub fn calibration_sum(input: &str, vocabulary: &Vocabulary) -> Result<u64, ParseError> {
+    let matcher = Matcher::new(vocabulary);
+    parse::lines(DAY, input).map(|line| calibration_value(line, &matcher).map(u64::from)).sum()
+}
+
+/// Sums calibration values as the input arrives, in chunks of any size. Only the matcher's state and the current
+/// line's first and last tokens are kept, never the line itself, so the input can be as big as it likes.
+#[derive(Clone, Debug)]
+pub struct Calibrator<'a> {
+    matcher: &'a Matcher,
+    state: usize,
+    // 1-based
+    line: usize,
+    // bytes into the current line
+    offset: usize,
+    ends: Option<(Token, Token)>,
+    sum: u64,
+}
+
+impl<'a> Calibrator<'a> {
+    /// Starts at the beginning of an input.
+    pub fn new(matcher: &'a Matcher) -> Self {
+        Calibrator { matcher, state: 0, line: 1, offset: 0, ends: None, sum: 0 }
+    }
+
+    /// Reads the next chunk of input, which can end partway through a line (or a word).
+    pub fn feed(&mut self, bytes: &[u8]) -> Result<(), ParseError> {
+        for &b in bytes {
+            if b == b'\n' {
+                self.end_line()?;
+                continue;
+            }
+            self.offset += 1;
+            self.state = self.matcher.transitions[self.state][b as usize] as usize;
+            for &(len, value) in &self.matcher.outputs[self.state] {
+                self.ends = Some(extend(self.ends, Token { start: self.offset - len, end: self.offset, value }));
+            }
+        }
+        Ok(())
+    }
+
+    fn end_line(&mut self) -> Result<(), ParseError> {
+        // the line's gone by now, so the error can't quote it
+        let ends = self.ends.take().ok_or_else(|| ParseError::new(DAY, self.line, 1, "", "no digit on this line"))?;
+        self.sum += u64::from(value(ends));
+        self.line += 1;
+        self.offset = 0;
+        self.state = 0;
+        Ok(())
+    }
+
+    /// The sum, once all the input has been fed in (the last line doesn't need a newline).
+    pub fn finish(mut self) -> Result<u64, ParseError> {
+        if self.offset > 0 {
+            self.end_line()?;
+        }
+        Ok(self.sum)
+    }
+}
+
+/// Like [`calibration_sum`], but reads `reader` a chunk at a time, in constant memory (gigabytes from stdin are
+/// fine). A line without a digit is an [`io::ErrorKind::InvalidData`] error.
+pub fn calibration_sum_streaming(mut reader: impl Read, vocabulary: &Vocabulary) -> io::Result<u64> {
+    let matcher = Matcher::new(vocabulary);
+    let mut calibrator = Calibrator::new(&matcher);
+    let mut buffer = vec![0; 64 * 1024];
+    let invalid = |e: ParseError| io::Error::new(io::ErrorKind::InvalidData, e);
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => return calibrator.finish().map_err(invalid),
+            Ok(n) => calibrator.feed(&buffer[..n]).map_err(invalid)?,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// How one way of reading a line went: every token found, and the first and last of them, if there were any.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Reading {
+    /// Every token on the line, in order of where they end.
+    pub tokens: Vec<Token>,
+    /// The tokens the calibration value comes from.
+    pub first_and_last: Option<(Token, Token)>,
+}
+
+impl Reading {
+    fn new(line: &str, matcher: &Matcher) -> Self {
+        let tokens = matcher.tokens(line).collect::<Vec<_>>();
+        let first_and_last = first_and_last(tokens.iter().copied());
+        Reading { tokens, first_and_last }
+    }
+
+    /// The calibration value, unless the line has no digit.
+    pub fn value(&self) -> Option<u32> {
+        self.first_and_last.map(value)
+    }
+}
+
+/// How a line's calibration value is worked out in each part, for finding the line that's been misread.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Explanation<'a> {
+    /// 1-based line number.
+    pub line: usize,
+    /// The line itself.
+    pub text: &'a str,
+    /// The line read with only digits (part 1) and with spelled digits too (part 2).
+    pub readings: [Reading; 2],
+}
+
+impl Explanation<'_> {
+    /// Whether the two parts give the line different values.
+    pub fn disagrees(&self) -> bool {
+        self.readings[0].value() != self.readings[1].value()
+    }
+}
+
+impl Display for Explanation<'_> {
+    // line 2: eightwothree
+    //   part 1: no digit
+    //   part 2: eight@0 two@4 three@7, first eight@0, last three@7 = 83
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let token = |t: &Token| format!("{}@{}", &self.text[t.start..t.end], t.start);
+        write!(f, "line {}: {}", self.line, self.text)?;
+        for (part, reading) in self.readings.iter().enumerate() {
+            write!(f, "\n  part {}: ", part + 1)?;
+            match reading.first_and_last {
+                None => write!(f, "no digit")?,
+                Some((first, last)) => write!(
+                    f,
+                    "{}, first {}, last {} = {}",
+                    reading.tokens.iter().map(token).collect::<Vec<_>>().join(" "),
+                    token(&first),
+                    token(&last),
+                    value((first, last)),
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Explains every line of `input` (or, with `only_disagreements`, just the ones the parts read differently), with
+/// the tokens each part finds at their byte offsets.
+pub fn explain(input: &str, only_disagreements: bool) -> Vec<Explanation<'_>> {
+    let matchers = [Matcher::new(&Vocabulary::digits()), Matcher::new(&Vocabulary::english())];
+    parse::lines(DAY, input)
+        .map(|line| Explanation {
+            line: line.number,
+            text: line.text,
+            readings: [Reading::new(line.text, &matchers[0]), Reading::new(line.text, &matchers[1])],
+        })
+        .filter(|explanation| !only_disagreements || explanation.disagrees())
+        .collect()
+}
+
+/// Sums the calibration values made of each line's first and last digit.
+pub fn part1(input: String) -> SolveResult {
+    Ok(Solution::new(calibration_sum(&input, &Vocabulary::digits())?))
+}
+
+/// Like [`part1`], but digits can also be spelled out (`one`, `two`, ...).
+pub fn part2(input: String) -> SolveResult {
+    Ok(Solution::new(calibration_sum(&input, &Vocabulary::english())?))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+    use super::*;
+    use crate::testing::{check, property};
+
+    const EXAMPLE_1: &str = "\
+1abc2
+pqr3stu8vwx
+a1b2c3d4e5f
+treb7uchet
+";
+
+    const EXAMPLE_2: &str = "\
+two1nine
+eightwothree
+abcone2threexyz
+xtwone3four
+4nineeightseven2
+zoneight234
+7pqrstsixteen
+";
+
+    #[test]
+    fn part1_example() {
+        check(part1, EXAMPLE_1, 142);
+    }
+
+    #[test]
+    fn part2_example() {
+        check(part2, EXAMPLE_2, 281);
+    }
+
+    fn values(line: &str, vocabulary: &Vocabulary) -> Vec<u32> {
+        Matcher::new(vocabulary).tokens(line).map(|t| t.value).collect()
+    }
+
+    #[test]
+    fn overlapping_words_are_all_found() {
+        assert_eq!(values("xtwone3four", &Vocabulary::english()), [2, 1, 3, 4]);
+        assert_eq!(values("eightwo", &Vocabulary::english()), [8, 2]);
+        assert!(values("oneight", &Vocabulary::digits()).is_empty());
+        assert_eq!(
+            Matcher::new(&Vocabulary::english()).tokens("zoneight").collect::<Vec<_>>(),
+            [Token { start: 1, end: 4, value: 1 }, Token { start: 3, end: 8, value: 8 }],
+        );
+    }
+
+    // the automaton should find exactly what trying every word at every position finds
+    #[test]
+    fn matcher_matches_naive_search() {
+        property("matcher_matches_naive_search", 100, |rng| {
+            let vocabulary = Vocabulary::english().with_teens();
+            let pieces = ["on", "e", "tw", "o", "ni", "ne", "eight", "seven", "teen", "1", "x", "thr", "ee"];
+            let line = (0..rng.gen_range(1..12)).map(|_| pieces[rng.gen_range(0..pieces.len())]).collect::<String>();
+
+            let mut expected = (0..line.len()).flat_map(|start| {
+                let line = &line;
+                vocabulary.words.iter()
+                    .filter(move |(word, _)| line[start..].starts_with(word.as_str()))
+                    .map(move |(word, value)| Token { start, end: start + word.len(), value: *value })
+            }).collect::<Vec<_>>();
+            expected.sort_by_key(|t| (t.end, Reverse(t.start)));
+            assert_eq!(Matcher::new(&vocabulary).tokens(&line).collect::<Vec<_>>(), expected, "{line}");
+        });
+    }
+
+    // however the input is split into chunks, streaming should give what reading it all at once does
+    #[test]
+    fn streaming_matches_in_memory() {
+        property("streaming_matches_in_memory", 100, |rng| {
+            let pieces = ["on", "e", "tw", "o", "ni", "ne", "eight", "seven", "teen", "1", "x", "7"];
+            let input = (0..rng.gen_range(1..20)).map(|_| {
+                let line = (0..rng.gen_range(1..8)).map(|_| pieces[rng.gen_range(0..pieces.len())]).collect::<String>();
+                line + "1\n"
+            }).collect::<String>();
+            let input = if rng.gen_bool(0.5) { input.trim_end() } else { &input };
+
+            let vocabulary = Vocabulary::english().with_teens();
+            let matcher = Matcher::new(&vocabulary);
+            let mut calibrator = Calibrator::new(&matcher);
+            let mut rest = input.as_bytes();
+            while !rest.is_empty() {
+                let (chunk, tail) = rest.split_at(rng.gen_range(1..=rest.len().min(10)));
+                calibrator.feed(chunk).unwrap();
+                rest = tail;
+            }
+            assert_eq!(calibrator.finish().unwrap(), calibration_sum(input, &vocabulary).unwrap(), "{input}");
+
+            let digits = Vocabulary::digits();
+            assert_eq!(
+                calibration_sum_streaming(input.as_bytes(), &digits).unwrap(),
+                calibration_sum(input, &digits).unwrap(),
+            );
+        });
+    }
+
+    #[test]
+    fn streaming_errors_have_line_numbers() {
+        let error = calibration_sum_streaming("1abc2\n\nabc".as_bytes(), &Vocabulary::digits()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "day 1, line 2, column 1: no digit on this line");
+        assert_eq!(calibration_sum_streaming("two1nine".as_bytes(), &Vocabulary::english()).unwrap(), 29);
+    }
 
-            let mut out = String::with_capacity(2);
-            out.push(*s.chars().filter(|c| c.is_numeric()).collect::<Vec<char>>().first().unwrap());
-            out.push(*s_rev.chars().filter(|c| c.is_numeric()).collect::<Vec<char>>().first().unwrap());
-            i32::from_str(&out).unwrap()
-        }).sum::<i32>());
-}
-
-fn replace(slice: &[char], s: &str) -> String {
-    match slice {
-        &['z', 'e', 'r', 'o', _] => s.replacen("zero", "0", 1),
-        &['o', 'n', 'e', _, _] => s.replacen("one", "1", 1),
-        &['t', 'w', 'o', _, _] => s.replacen("two", "2", 1),
-        &['t', 'h', 'r', 'e', 'e'] => s.replacen("three", "3", 1),
-        &['f', 'o', 'u', 'r', _] => s.replacen("four", "4", 1),
-        &['f', 'i', 'v', 'e', _] => s.replacen("five", "5", 1),
-        &['s', 'i', 'x', _, _] => s.replacen("six", "6", 1),
-        &['s', 'e', 'v', 'e', 'n'] => s.replacen("seven", "7", 1),
-        &['e', 'i', 'g', 'h', 't'] => s.replacen("eight", "8", 1),
-        &['n', 'i', 'n', 'e', _] => s.replacen("nine", "9", 1),
-        _ => s.to_string(),
-    }
-}
-
-fn replace_rev(slice: &[char], s: &str) -> String {
-    match slice {
-        &['o', 'r', 'e', 'z', _] => s.replacen("orez", "0", 1),
-        &['e', 'n', 'o', _, _] => s.replacen("eno", "1", 1),
-        &['o', 'w', 't', _, _] => s.replacen("owt", "2", 1),
-        &['e', 'e', 'r', 'h', 't'] => s.replacen("eerht", "3", 1),
-        &['r', 'u', 'o', 'f', _] => s.replacen("ruof", "4", 1),
-        &['e', 'v', 'i', 'f', _] => s.replacen("evif", "5", 1),
-        &['x', 'i', 's', _, _] => s.replacen("xis", "6", 1),
-        &['n', 'e', 'v', 'e', 's'] => s.replacen("neves", "7", 1),
-        &['t', 'h', 'g', 'i', 'e'] => s.replacen("thgie", "8", 1),
-        &['e', 'n', 'i', 'n', _] => s.replacen("enin", "9", 1),
-        _ => s.to_string(),
-    }
-}
\ No newline at end of file
+    #[test]
+    fn other_vocabularies() {
+        let sum = |input: &str, vocabulary| calibration_sum(input, &vocabulary).unwrap();
+        assert_eq!(sum("seventeen\nabtwelvexsix\n", Vocabulary::english().with_teens()), 17 + 16);
+        assert_eq!(sum("seventeen\n", Vocabulary::english()), 77);
+        assert_eq!(sum("zweiundfünfzig\nachteins\n", Vocabulary::spelled(&GERMAN)), 25 + 81);
+        assert_eq!(sum("troisept\n", Vocabulary::spelled(&FRENCH)), 37);
+    }
+
+    #[test]
+    fn explanations() {
+        let explanations = explain(EXAMPLE_2, true);
+        assert_eq!(explanations.iter().map(|e| e.line).collect::<Vec<_>>(), [1, 2, 3, 4, 6, 7]);
+        assert_eq!(explanations[1].to_string(), "\
+line 2: eightwothree
+  part 1: no digit
+  part 2: eight@0 two@4 three@7, first eight@0, last three@7 = 83");
+        assert_eq!(explanations[3].to_string(), "\
+line 4: xtwone3four
+  part 1: 3@6, first 3@6, last 3@6 = 33
+  part 2: two@1 one@3 3@6 four@7, first two@1, last four@7 = 24");
+
+        // 4nineeightseven2 is 42 either way
+        assert_eq!(explain(EXAMPLE_2, false).len(), 7);
+        assert!(!explain(EXAMPLE_2, false)[4].disagrees());
+    }
+
+    #[test]
+    fn lines_without_digits_are_errors() {
+        let error = part1("1abc2\nabc\n".to_string()).unwrap_err();
+        assert_eq!((error.line, error.column, error.message.as_str()), (2, 1, "no digit on this line"));
+        assert!(part2("1abc2\none\n".to_string()).is_ok());
+    }
+}
@@ -1,14 +1,44 @@
 use std::collections::HashSet;
+use crate::grid::Grid;
+use crate::parse::{self, ParseError};
+use crate::render::{Frame, Rgb};
+use crate::solution::{Solution, SolveResult};
 
-fn find_start(grid: &Vec<Vec<char>>) -> (usize, usize) {
-    for (y, line) in grid.iter().enumerate() {
-        for (x, c) in line.iter().enumerate() {
-            if *c == 'S' {
-                return (x, y);
-            }
-        }
+// where S is on a row, and what connects to it, depends on the rows around it, so only the tiles are checked a row
+// at a time
+crate::registry::puzzle!(2023, 10, "Pipe Maze", part1, part2, |input| {
+    match parse::line_errors(input, |input| Grid::parse(DAY, input, tile)) {
+        errors if errors.is_empty() => parse::errors(parse_input(input.to_string())),
+        errors => errors,
+    }
+});
+
+fn tile(c: char) -> Option<char> {
+    "|-LJ7F.S".contains(c).then_some(c)
+}
+
+// an error at a tile of the map
+fn error_at(grid: &Grid<char>, (x, y): (usize, usize), message: impl Into<String>) -> ParseError {
+    ParseError::new(DAY, y + 1, x + 1, &grid[(x, y)].to_string(), message)
+}
+
+/// Parses the pipe map and finds the starting position, checking exactly two pipes connect to it.
+pub fn parse_input(input: String) -> Result<(Grid<char>, (usize, usize)), ParseError> {
+    let grid = Grid::parse(DAY, &input, tile)?;
+    let start = grid.position(|&c| c == 'S').ok_or_else(|| ParseError::whole_input(DAY, "grid does not contain a starting position (S)"))?;
+    let connected = get_adjacent_pipes(&grid, start).len();
+    if connected != 2 {
+        return Err(error_at(&grid, start, format!("the start should connect to 2 pipes, but it connects to {connected}")));
     }
-    panic!("Grid does not contain starting position!");
+    Ok((grid, start))
+}
+
+// the next tile along the loop from `pos`, having come from `prev`
+fn next_pipe(grid: &Grid<char>, pos: (usize, usize), prev: (usize, usize)) -> Result<(usize, usize), ParseError> {
+    get_adjacent_pipes(grid, pos)
+        .into_iter()
+        .find(|&p| p != prev)
+        .ok_or_else(|| error_at(grid, pos, "the loop from S comes to a dead end here"))
 }
 
 fn connects_down(c: char) -> bool {
@@ -27,88 +57,35 @@ fn connects_right(c: char) -> bool {
     "-LFS".contains(c)
 }
 
-fn get_adjacent_pipes(grid: &Vec<Vec<char>>, pos: (usize, usize)) -> Vec<(usize, usize)> {
+/// The tiles the pipe at `pos` connects to (for `S`, every neighbour that connects back to it).
+pub fn get_adjacent_pipes(grid: &Grid<char>, pos: (usize, usize)) -> Vec<(usize, usize)> {
     let mut v = Vec::with_capacity(2);
-    let (x, y) = pos;
-    let c = grid[y][x];
-
-    // oh god
-    match c {
-        'S' => {
-            if connects_down(grid[y-1][x]) {
-                v.push((x, y-1));
-            }
-            if connects_up(grid[y+1][x]) {
-                v.push((x, y+1));
-            }
-            if connects_left(grid[y][x+1]) {
-                v.push((x+1, y));
-            }
-            if connects_right(grid[y][x-1]) {
-                v.push((x-1, y));
-            }
+    let mut connect = |offset, connects_back: fn(char) -> bool| {
+        if let Some(p) = grid.offset(pos, offset).filter(|&p| connects_back(grid[p])) {
+            v.push(p);
         }
-        '|' => {
-            if connects_down(grid[y-1][x]) {
-                v.push((x, y-1));
-            }
-            if connects_up(grid[y+1][x]) {
-                v.push((x, y+1));
-            }
-        }
-        '-' => {
-            if connects_left(grid[y][x+1]) {
-                v.push((x+1, y));
-            }
-            if connects_right(grid[y][x-1]) {
-                v.push((x-1, y));
-            }
-        }
-        'L' => {
-            if connects_down(grid[y-1][x]) {
-                v.push((x, y-1));
-            }
-            if connects_left(grid[y][x+1]) {
-                v.push((x+1, y));
-            }
-        }
-        'J' => {
-            if connects_down(grid[y-1][x]) {
-                v.push((x, y-1));
-            }
-            if connects_right(grid[y][x-1]) {
-                v.push((x-1, y));
-            }
-        }
-        '7' => {
-            if connects_up(grid[y+1][x]) {
-                v.push((x, y+1));
-            }
-            if connects_right(grid[y][x-1]) {
-                v.push((x-1, y));
-            }
-        }
-        'F' => {
-            if connects_up(grid[y+1][x]) {
-                v.push((x, y+1));
-            }
-            if connects_left(grid[y][x+1]) {
-                v.push((x+1, y));
-            }
-        }
-        _ => {}
+    };
+    let c = grid[pos];
+
+    if connects_up(c) {
+        connect((0, -1), connects_down);
+    }
+    if connects_down(c) {
+        connect((0, 1), connects_up);
+    }
+    if connects_right(c) {
+        connect((1, 0), connects_left);
+    }
+    if connects_left(c) {
+        connect((-1, 0), connects_right);
     }
 
     v
 }
 
-pub(crate) fn part1(input: String) {
-    let grid = input
-        .lines()
-        .map(|l| l.chars().collect::<Vec<_>>())
-        .collect::<Vec<_>>();
-
-    let (start_x, start_y) = find_start(&grid);
+/// The number of steps to the point of the loop farthest from the start.
+pub fn part1(input: String) -> SolveResult {
+    let (grid, (start_x, start_y)) = parse_input(input)?;
     let starts = get_adjacent_pipes(&grid, (start_x, start_y));
     let ((mut x1, mut y1), (mut x2, mut y2)) = (starts[0], starts[1]);
     let (mut px1, mut py1) = (start_x, start_y);
@@ -116,9 +93,9 @@ pub(crate) fn part1(input: String) {
 
     let mut distance = 1;
 
-    println!("{}", loop {
-        let (nx1, ny1) = get_adjacent_pipes(&grid, (x1, y1)).into_iter().filter(|p| *p != (px1, py1)).next().unwrap();
-        let (nx2, ny2) = get_adjacent_pipes(&grid, (x2, y2)).into_iter().filter(|p| *p != (px2, py2)).next().unwrap();
+    Ok(Solution::new(loop {
+        let (nx1, ny1) = next_pipe(&grid, (x1, y1), (px1, py1))?;
+        let (nx2, ny2) = next_pipe(&grid, (x2, y2), (px2, py2))?;
         (px1, py1) = (x1, y1);
         (px2, py2) = (x2, y2);
         (x1, y1) = (nx1, ny1);
@@ -128,15 +105,30 @@ pub(crate) fn part1(input: String) {
         if (x1, y1) == (x2, y2) {
             break distance;
         }
-    });
+    }))
 }
 
-pub(crate) fn part2(input: String) {
-    let grid = input
-        .lines()
-        .map(|l| l.chars().collect::<Vec<_>>())
-        .collect::<Vec<_>>();
-    let (start_x, start_y) = find_start(&grid);
+// the pipe hidden under the S, worked out from the two pipes that connect to it
+fn start_pipe(grid: &Grid<char>, start: (usize, usize)) -> char {
+    let (x, y) = start;
+    let adjacent = get_adjacent_pipes(grid, start);
+    let up = adjacent.contains(&(x, y.wrapping_sub(1)));
+    let down = adjacent.contains(&(x, y + 1));
+    let left = adjacent.contains(&(x.wrapping_sub(1), y));
+    let right = adjacent.contains(&(x + 1, y));
+    match (up, down, left, right) {
+        (true, true, _, _) => '|',
+        (_, _, true, true) => '-',
+        (true, _, true, _) => 'J',
+        (true, _, _, true) => 'L',
+        (_, true, true, _) => '7',
+        _ => 'F',
+    }
+}
+
+/// The number of tiles enclosed by the loop.
+pub fn part2(input: String) -> SolveResult {
+    let (mut grid, (start_x, start_y)) = parse_input(input)?;
     let (mut x, mut y) = get_adjacent_pipes(&grid, (start_x, start_y))[0];
     let (mut px, mut py) = (start_x, start_y);
 
@@ -145,7 +137,7 @@ pub(crate) fn part2(input: String) {
 
     loop {
         pipe_tiles.insert((x, y));
-        let (nx, ny) = get_adjacent_pipes(&grid, (x, y)).into_iter().filter(|p| *p != (px, py)).next().unwrap();
+        let (nx, ny) = next_pipe(&grid, (x, y), (px, py))?;
         (px, py) = (x, y);
         (x, y) = (nx, ny);
         if (x, y) == (start_x, start_y) {
@@ -153,13 +145,16 @@ pub(crate) fn part2(input: String) {
         }
     }
 
+    grid[(start_x, start_y)] = start_pipe(&grid, (start_x, start_y));
+
     let mut count = 0;
-    for (y, line) in grid.iter().enumerate() {
+    let mut frame = Frame::new(grid.width(), grid.height());
+    for (y, line) in grid.rows().enumerate() {
         let mut inside = false;
         let mut entry_connects_down = false;
         for (x, c) in line.iter().enumerate() {
             if pipe_tiles.contains(&(x, y)) {
-                if connects_up(*c) && connects_down(*c) && connects_down(grid[y-1][x]) && connects_up(grid[y+1][x]) {
+                if connects_up(*c) && connects_down(*c) && connects_down(grid[(x, y - 1)]) && connects_up(grid[(x, y + 1)]) {
                     inside = !inside;
                 } else if connects_right(*c) && (connects_up(*c) || connects_down(*c)) {
                     entry_connects_down = connects_down(*c);
@@ -168,7 +163,7 @@ pub(crate) fn part2(input: String) {
                         inside = !inside;
                     }
                 }
-                print!("{}", match *c {
+                let glyph = match *c {
                     '|' => '│',
                     '-' => '─',
                     'L' => '╰',
@@ -176,16 +171,110 @@ pub(crate) fn part2(input: String) {
                     'F' => '╭',
                     '7' => '╮',
                     _ => *c,
-                });
+                };
+                frame.set(x, y, glyph, None);
             } else if inside {
-                print!("█");
+                frame.set(x, y, '█', Some(Rgb(0, 192, 0)));
                 count += 1;
-            } else {
-                print!(" ");
             }
         }
-        println!();
     }
 
-    println!("{count}");
+    Ok(Solution::new(count).with_frame(frame))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::check;
+
+    const EXAMPLE_1: &str = "\
+-L|F7
+7S-7|
+L|7||
+-L-J|
+L|-JF
+";
+
+    const EXAMPLE_2: &str = "\
+7-F7-
+.FJ|7
+SJLL7
+|F--J
+LJ.LJ
+";
+
+    const EXAMPLE_3: &str = "\
+...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........
+";
+
+    const EXAMPLE_4: &str = "\
+.F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ...
+";
+
+    const EXAMPLE_5: &str = "\
+FF7FSF7F7F7F7F7F---7
+L|LJ||||||||||||F--J
+FL-7LJLJ||||||LJL-77
+F--JF--7||LJLJ7F7FJ-
+L---JF-JLJ.||-FJLJJ7
+|F|F-JF---7F7-L7L|7|
+|FFJF7L7F-JF7|JL---7
+7-L-JL7||F7|L7F-7F7|
+L.L7LFJ|||||FJL7||LJ
+L7JLJL-JLJLJL--JLJ.L
+";
+
+    #[test]
+    fn part1_example_1() {
+        check(part1, EXAMPLE_1, 4);
+    }
+
+    #[test]
+    fn part1_example_2() {
+        check(part1, EXAMPLE_2, 8);
+    }
+
+    #[test]
+    fn part2_example_1() {
+        check(part2, EXAMPLE_3, 4);
+    }
+
+    #[test]
+    fn part2_example_2() {
+        check(part2, EXAMPLE_4, 8);
+    }
+
+    #[test]
+    fn part2_example_3() {
+        check(part2, EXAMPLE_5, 10);
+    }
+
+    #[test]
+    fn broken_loops() {
+        let error = |input: &str| part1(input.to_string()).unwrap_err().to_string();
+        assert_eq!(
+            error(".....\n.S-..\n.....\n"),
+            "day 10, line 2, column 2: the start should connect to 2 pipes, but it connects to 1 (found `S`)",
+        );
+        assert_eq!(error(".....\n.S-7.\n.|.|.\n.L-..\n"), "day 10, line 4, column 3: the loop from S comes to a dead end here (found `-`)");
+        assert_eq!(part2(".....\n.S-7.\n.|.|.\n.L-..\n".to_string()).unwrap_err().line, 4);
+    }
 }
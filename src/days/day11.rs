@@ -1,24 +1,28 @@
 use std::collections::{HashMap, HashSet};
+use crate::grid::Grid;
+use crate::parse::{self, ParseError};
+use crate::solution::{Solution, SolveResult};
 
-fn get_expanded_grid(input: String, insert_rows: usize) -> HashMap<(usize, usize), HashSet<(usize, usize)>> {
+crate::registry::puzzle!(2023, 11, "Cosmic Expansion", part1, part2, |input| {
+    parse::line_errors(input, |input| get_expanded_grid(input.to_string(), 1))
+});
+
+/// The positions of the galaxies once every empty row and column has had `insert_rows` more inserted after it.
+/// Each galaxy maps to the set of galaxies it has already been paired with (initially just itself).
+pub fn get_expanded_grid(input: String, insert_rows: usize) -> Result<HashMap<(usize, usize), HashSet<(usize, usize)>>, ParseError> {
     let mut h = HashMap::new();
 
-    let grid = input.lines().map(|l| l.chars().collect::<Vec<_>>()).collect::<Vec<_>>();
+    let grid = Grid::parse(DAY, &input, |c| matches!(c, '.' | '#').then_some(c))?;
     let mut ex = 0;
     let mut ey = 0;
-    for (oy, l) in grid.iter().enumerate() {
+    for l in grid.rows() {
         let mut empty_row = true;
         for (ox, &c) in l.iter().enumerate() {
             if c == '#' {
                 empty_row = false;
                 h.insert((ex, ey), HashSet::from([(ex, ey)]));
             }
-            let mut empty_col = true;
-            for y in 0..grid.len() {
-                if grid[y][ox] == '#' {
-                    empty_col = false;
-                }
-            }
+            let empty_col = grid.column(ox).all(|&c| c != '#');
             ex += 1;
             if empty_col {
                 ex += insert_rows;
@@ -31,11 +35,11 @@ fn get_expanded_grid(input: String, insert_rows: usize) -> HashMap<(usize, usize
         }
     }
 
-    h
+    Ok(h)
 }
 
-fn day11(input: String, insert_rows: usize) {
-    let mut galaxies = get_expanded_grid(input, insert_rows);
+fn day11(input: String, insert_rows: usize) -> SolveResult {
+    let mut galaxies = get_expanded_grid(input, insert_rows)?;
 
     let mut total_distance = 0;
 
@@ -50,13 +54,49 @@ fn day11(input: String, insert_rows: usize) {
         }
     }
 
-    println!("{total_distance}");
+    Ok(Solution::new(total_distance))
+}
+
+/// Sums the distances between every pair of galaxies, with empty rows and columns doubled.
+pub fn part1(input: String) -> SolveResult {
+    day11(input, 1)
 }
 
-pub(crate) fn part1(input: String) {
-    day11(input, 1);
+/// Like [`part1`], but every empty row and column is a million times bigger.
+pub fn part2(input: String) -> SolveResult {
+    day11(input, 999999)
 }
 
-pub(crate) fn part2(input: String) {
-    day11(input, 999999);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::check;
+
+    const EXAMPLE: &str = "\
+...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....
+";
+
+    #[test]
+    fn part1_example() {
+        check(part1, EXAMPLE, 374);
+    }
+
+    #[test]
+    fn expansion_by_10() {
+        check(|input| day11(input, 9), EXAMPLE, 1030);
+    }
+
+    #[test]
+    fn expansion_by_100() {
+        check(|input| day11(input, 99), EXAMPLE, 8410);
+    }
 }
@@ -1,7 +1,14 @@
 use std::collections::HashMap;
-use std::str::FromStr;
+use crate::parse::{self, Line, ParseError};
+use crate::solution::{Solution, SolveResult};
 
-fn get_arrs(springs: &[Option<bool>], groups: &[i32]) -> usize {
+crate::registry::puzzle!(2023, 12, "Hot Springs", part1, part2, |input| {
+    parse::line_errors(input, |input| parse::lines(DAY, input).try_for_each(|line| parse_row(line, false).map(drop)))
+});
+
+/// Counts the arrangements of operational (`Some(true)`), damaged (`Some(false)`) and unknown (`None`) springs
+/// that match the sizes of the contiguous damaged groups.
+pub fn get_arrs(springs: &[Option<bool>], groups: &[i32]) -> usize {
     fn get_arrs_internal(springs: &[Option<bool>], offs: usize, idx: usize, groups: &[i32], memos: &mut HashMap<(usize, usize), usize>) -> usize {
         if let Some(&count) = memos.get(&(offs, idx)) {
             return count;
@@ -70,41 +77,87 @@ fn get_arrs(springs: &[Option<bool>], groups: &[i32]) -> usize {
     get_arrs_internal(springs, 0, 0, groups, &mut HashMap::new())
 }
 
-fn day12(input: String, part2: bool) {
-    println!("{}", input
-        .lines()
-        .map(|l| l.split_once(' ').map(|(springs, groups)| {
-            let mut s = String::from(springs);
-            let mut g = String::from(groups);
-            if part2 {
-                for _ in 0..4 {
-                    s.push('?');
-                    s.push_str(springs);
-
-                    g.push(',');
-                    g.push_str(groups);
-                }
-            }
-            let springs = s.chars().map(|c| match c {
-                '?' => None,
-                '.' => Some(true),
-                '#' => Some(false),
-                _ => panic!("Invalid char in springs"),
-            }).collect::<Vec<_>>();
-            let groups = g
-                .split(',')
-                .map(|s| i32::from_str(s).unwrap())
-                .collect::<Vec<_>>();
-            get_arrs(&springs, &groups)
-        }).unwrap())
+/// Parses a `<springs> <groups>` row; with `part2` it's unfolded into five copies.
+pub fn parse_row(line: Line, part2: bool) -> Result<(Vec<Option<bool>>, Vec<i32>), ParseError> {
+    let (springs, groups) = line.split_once(line.text, " ")?;
+    if let Some((i, c)) = springs.char_indices().find(|(_, c)| !"?.#".contains(*c)) {
+        return Err(line.error_at(i, &c.to_string(), "invalid char in springs"));
+    }
+    let groups = groups
+        .split(',')
+        .map(|s| match line.parse(s, "group size")? {
+            n if n < 1 => Err(line.error(s.trim(), "group sizes must be at least 1")),
+            n => Ok(n),
+        })
+        .collect::<Result<Vec<i32>, _>>()?;
+
+    let mut s = String::from(springs);
+    let mut g = groups.clone();
+    if part2 {
+        for _ in 0..4 {
+            s.push('?');
+            s.push_str(springs);
+
+            g.extend_from_slice(&groups);
+        }
+    }
+    let springs = s.chars().map(|c| match c {
+        '?' => None,
+        '.' => Some(true),
+        _ => Some(false),
+    }).collect::<Vec<_>>();
+
+    Ok((springs, g))
+}
+
+fn day12(input: String, part2: bool) -> SolveResult {
+    let rows = parse::lines(DAY, &input)
+        .map(|l| parse_row(l, part2))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Solution::new(rows
+        .iter()
+        .map(|(springs, groups)| get_arrs(springs, groups))
         .sum::<usize>()
-    );
+    ))
+}
+
+/// Sums the number of possible arrangements of every row.
+pub fn part1(input: String) -> SolveResult {
+    day12(input, false)
 }
 
-pub(crate) fn part1(input: String) {
-    day12(input, false);
+/// Like [`part1`], but with every row unfolded.
+pub fn part2(input: String) -> SolveResult {
+    day12(input, true)
 }
 
-pub(crate) fn part2(input: String) {
-    day12(input, true);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::check;
+
+    const EXAMPLE: &str = "\
+???.### 1,1,3
+.??..??...?##. 1,1,3
+?#?#?#?#?#?#?#? 1,3,1,6
+????.#...#... 4,1,1
+????.######..#####. 1,6,5
+?###???????? 3,2,1
+";
+
+    #[test]
+    fn part1_example() {
+        check(part1, EXAMPLE, 21);
+    }
+
+    #[test]
+    fn part2_example() {
+        check(part2, EXAMPLE, 525152);
+    }
+
+    #[test]
+    fn group_sizes_must_be_positive() {
+        let error = part1("???.### 1,-1,3\n".to_string()).unwrap_err();
+        assert_eq!(error.to_string(), "day 12, line 1, column 11: group sizes must be at least 1 (found `-1`)");
+    }
 }
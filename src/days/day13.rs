@@ -1,103 +1,87 @@
+use crate::grid::Grid;
+use crate::parse::{self, ParseError};
+use crate::solution::{Solution, SolveResult};
 use Reflection::*;
 
+crate::registry::puzzle!(2023, 13, "Point of Incidence", part1, part2, |input| {
+    parse::line_errors(input, |input| parse_grids(input.to_string()))
+});
+
+/// A line of reflection in a pattern.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
-enum Reflection {
-    Vertical { col: usize },
-    Horizontal { row: usize },
+pub enum Reflection {
+    /// Between column `col` and the one before it.
+    Vertical {
+        /// The number of columns left of the line.
+        col: usize,
+    },
+    /// Between row `row` and the one before it.
+    Horizontal {
+        /// The number of rows above the line.
+        row: usize,
+    },
 }
 
-fn find_reflection(grid: &Vec<Vec<bool>>, existing: Option<Reflection>) -> Option<Reflection> {
-    for idx in 0..grid.len() - 1 {
-        let mut reflection = true;
-        let mut upper = idx + 1;
-        let mut lower = idx;
-        loop {
-            if grid[upper] != grid[lower] {
-                reflection = false;
-            }
-
-            if lower == 0 || upper == grid.len() - 1 {
-                break;
-            }
-
-            lower -= 1;
-            upper += 1;
-        }
-        if reflection {
-            let r = Some(Horizontal { row: idx + 1 });
-            if r != existing {
-                return r;
-            }
-        }
-    }
-
-    for idx in 0..grid[0].len() - 1 {
-        let mut reflection = true;
-        let mut upper = idx + 1;
-        let mut lower = idx;
-        loop {
-            for y in 0..grid.len() {
-                if grid[y][upper] != grid[y][lower] {
-                    reflection = false;
-                }
-            }
-
-            if lower == 0 || upper == grid[0].len() - 1 {
-                break;
-            }
+// the first row (other than `skip`) that the pattern is mirrored above and below
+fn find_mirror_row(grid: &Grid<bool>, skip: Option<usize>) -> Option<usize> {
+    (1..grid.height()).filter(|&row| Some(row) != skip).find(|&row| {
+        (0..row.min(grid.height() - row)).all(|i| grid.row(row - 1 - i) == grid.row(row + i))
+    })
+}
 
-            lower -= 1;
-            upper += 1;
-        }
-        if reflection {
-            let r = Some(Vertical { col: idx + 1 });
-            if r != existing {
-                return r;
-            }
-        }
-    }
+/// Finds a line of reflection in a pattern (`true` for rocks), skipping `existing`.
+pub fn find_reflection(grid: &Grid<bool>, existing: Option<Reflection>) -> Option<Reflection> {
+    let skip_row = match existing {
+        Some(Horizontal { row }) => Some(row),
+        _ => None,
+    };
+    let skip_col = match existing {
+        Some(Vertical { col }) => Some(col),
+        _ => None,
+    };
 
-    None
+    find_mirror_row(grid, skip_row)
+        .map(|row| Horizontal { row })
+        .or_else(|| find_mirror_row(&grid.transpose(), skip_col).map(|col| Vertical { col }))
 }
 
-fn parse_grids(input: String) -> Vec<Vec<Vec<bool>>> {
-    input
-        .split("\n\n")
-        .map(|grid| grid.lines().map(|line| line.chars().map(|c| match c {
-            '.' => false,
-            '#' => true,
-            _ => panic!("invalid char in input!"),
-        })
-            .collect::<Vec<_>>())
-            .collect::<Vec<Vec<_>>>())
-        .collect::<Vec<Vec<Vec<_>>>>()
+/// Parses the blank-line separated patterns.
+pub fn parse_grids(input: String) -> Result<Vec<Grid<bool>>, ParseError> {
+    parse::sections(DAY, &input)
+        .iter()
+        .map(|grid| Grid::parse_lines(grid, |c| match c {
+            '.' => Some(false),
+            '#' => Some(true),
+            _ => None,
+        }))
+        .collect()
 }
 
-pub(crate) fn part1(input: String) {
-    let ans = parse_grids(input).iter().map(|grid| {
+/// Summarizes the line of reflection of every pattern (columns to its left, or 100 times the rows above it).
+pub fn part1(input: String) -> SolveResult {
+    let ans = parse_grids(input)?.iter().map(|grid| {
         let reflection = find_reflection(grid, None).unwrap();
         match reflection {
             Vertical { col: c } => c,
             Horizontal { row: r } => r * 100,
         }
     }).sum::<usize>();
-    println!("{ans}");
+    Ok(Solution::new(ans))
 }
 
-pub(crate) fn part2(input: String) {
-    let ans = parse_grids(input).iter().map(|grid| {
+/// Like [`part1`], but each pattern has exactly one smudge that has to be fixed to find a different line.
+pub fn part2(input: String) -> SolveResult {
+    let ans = parse_grids(input)?.iter().map(|grid| {
         let smudged_reflection = find_reflection(grid, None).unwrap();
         let mut real_reflection = None;
-        'outer: for y in 0..grid.len() {
-            for x in 0..grid[0].len() {
-                let mut desmudged = grid.clone();
-                desmudged[y][x] = !desmudged[y][x];
-                let reflection = find_reflection(&desmudged, Some(smudged_reflection));
-                if let Some(reflection) = reflection {
-                    if reflection != smudged_reflection {
-                        real_reflection = Some(reflection);
-                        break 'outer;
-                    }
+        for (x, y) in grid.positions() {
+            let mut desmudged = grid.clone();
+            desmudged[(x, y)] = !desmudged[(x, y)];
+            let reflection = find_reflection(&desmudged, Some(smudged_reflection));
+            if let Some(reflection) = reflection {
+                if reflection != smudged_reflection {
+                    real_reflection = Some(reflection);
+                    break;
                 }
             }
         }
@@ -107,5 +91,39 @@ pub(crate) fn part2(input: String) {
             Horizontal { row: r } => r * 100,
         }
     }).sum::<usize>();
-    println!("{ans}");
+    Ok(Solution::new(ans))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::check;
+
+    const EXAMPLE: &str = "\
+#.##..##.
+..#.##.#.
+##......#
+##......#
+..#.##.#.
+..##..##.
+#.#.##.#.
+
+#...##..#
+#....#..#
+..##..###
+#####.##.
+#####.##.
+..##..###
+#....#..#
+";
+
+    #[test]
+    fn part1_example() {
+        check(part1, EXAMPLE, 405);
+    }
+
+    #[test]
+    fn part2_example() {
+        check(part2, EXAMPLE, 400);
+    }
 }
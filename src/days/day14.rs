@@ -1,16 +1,29 @@
 use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
+use crate::cancel;
+use crate::grid::Grid;
+use crate::parse::{self, ParseError};
+use crate::solution::{Solution, SolveResult};
 
-pub(crate) fn part1(input: String) {
-    let grid = input.lines().map(|l| l.chars().collect::<Vec<_>>()).collect::<Vec<_>>();
-    let mut total = 0;
-    for x in 0..grid[0].len() {
+crate::registry::puzzle!(2023, 14, "Parabolic Reflector Dish", part1, part2, |input| {
+    parse::line_errors(input, |input| parse_input(input.to_string()))
+});
+
+/// Parses the platform: `O` rounded rocks, `#` cube rocks and `.` empty space.
+pub fn parse_input(input: String) -> Result<Grid<char>, ParseError> {
+    Grid::parse(DAY, &input, |c| matches!(c, '.' | '#' | 'O').then_some(c))
+}
+
+/// Rolls every rounded rock as far north as it goes.
+pub fn tilt_north(grid: &mut Grid<char>) {
+    for x in 0..grid.width() {
         let mut rounded_y = 0;
-        for y in 0..grid.len() {
-            match grid[y][x] {
+        for y in 0..grid.height() {
+            match grid[(x, y)] {
                 'O' => {
-                    total += grid.len() - rounded_y;
+                    grid[(x, y)] = '.';
+                    grid[(x, rounded_y)] = 'O';
                     rounded_y += 1;
                 },
                 '#' => rounded_y = y + 1,
@@ -18,7 +31,18 @@ pub(crate) fn part1(input: String) {
             }
         }
     }
-    println!("{total}");
+}
+
+/// The total load on the north support beams.
+pub fn north_load(grid: &Grid<char>) -> usize {
+    grid.iter().filter(|(_, &c)| c == 'O').map(|((_, y), _)| grid.height() - y).sum()
+}
+
+/// The total load on the north support beams after tilting the platform north.
+pub fn part1(input: String) -> SolveResult {
+    let mut grid = parse_input(input)?;
+    tilt_north(&mut grid);
+    Ok(Solution::new(north_load(&grid)))
 }
 
 fn hash<T: Hash>(t: &T) -> u64 {
@@ -27,81 +51,21 @@ fn hash<T: Hash>(t: &T) -> u64 {
     s.finish()
 }
 
-pub(crate) fn part2(input: String) {
-    let mut grid = input.lines().map(|l| l.chars().collect::<Vec<_>>()).collect::<Vec<_>>();
+/// The total load after a billion spin cycles (north, west, south, east).
+pub fn part2(input: String) -> SolveResult {
+    let mut grid = parse_input(input)?;
     let mut prev_states = HashMap::new();
     let mut prev_counts = vec![];
 
     for i in 0..1000000000usize {
-        // tilt north
-        for x in 0..grid[0].len() {
-            let mut rounded_y = 0;
-            for y in 0..grid.len() {
-                match grid[y][x] {
-                    'O' => {
-                        grid[y][x] = '.';
-                        grid[rounded_y][x] = 'O';
-                        rounded_y += 1;
-                    },
-                    '#' => rounded_y = y + 1,
-                    _ => {},
-                }
-            }
-        }
-
-        // tilt west
-        for y in 0..grid.len() {
-            let mut rounded_x = 0;
-            for x in 0..grid[0].len() {
-                match grid[y][x] {
-                    'O' => {
-                        grid[y][x] = '.';
-                        grid[y][rounded_x] = 'O';
-                        rounded_x += 1;
-                    },
-                    '#' => rounded_x = x + 1,
-                    _ => {},
-                }
-            }
-        }
-
-
-        // tilt south
-        for x in 0..grid[0].len() {
-            let mut rounded_y = grid.len() - 1;
-            for y in (0..grid.len()).rev() {
-                match grid[y][x] {
-                    'O' => {
-                        grid[y][x] = '.';
-                        grid[rounded_y][x] = 'O';
-                        rounded_y -= 1;
-                    },
-                    '#' => rounded_y = y - 1,
-                    _ => {},
-                }
-            }
+        cancel::checkpoint_with(|| format!("no arrangement repeated in the first {i} spin cycles"));
+        // tilting north then turning the platform clockwise brings west round to the north, then south, then east
+        for _ in 0..4 {
+            tilt_north(&mut grid);
+            grid = grid.rotate_clockwise();
         }
 
-        // tilt east
-        for y in 0..grid.len() {
-            let mut rounded_x = grid[0].len() - 1;
-            for x in (0..grid[0].len()).rev() {
-                match grid[y][x] {
-                    'O' => {
-                        grid[y][x] = '.';
-                        grid[y][rounded_x] = 'O';
-                        rounded_x -= 1;
-                    },
-                    '#' => rounded_x = x - 1,
-                    _ => {},
-                }
-            }
-        }
-
-        let l = grid.len();
-        let total = grid.iter().enumerate().map(|(y, line)| {
-            line.iter().map(move |&c| if c == 'O' { l - y } else { 0 })
-        }).flatten().sum::<usize>();
+        let total = north_load(&grid);
 
         let hash = hash(&grid);
         if let Some(it) = prev_states.get(&hash) {
@@ -109,11 +73,42 @@ pub(crate) fn part2(input: String) {
             let tgt = 1000000000 - it;
             let ofs = tgt % llen;
             let n = it + ofs - 1;
-            println!("{}", prev_counts[n]);
-            break;
+            log::debug!("spin cycle {} repeats cycle {}", i + 1, it + 1);
+            return Ok(Solution::new(prev_counts[n]));
         } else {
             prev_states.insert(hash, i);
             prev_counts.push(total);
         }
     }
+
+    Ok(Solution::new(*prev_counts.last().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::check;
+
+    const EXAMPLE: &str = "\
+O....#....
+O.OO#....#
+.....##...
+OO.#O....O
+.O.....O#.
+O.#..O.#.#
+..O..#O..O
+.......O..
+#....###..
+#OO..#....
+";
+
+    #[test]
+    fn part1_example() {
+        check(part1, EXAMPLE, 136);
+    }
+
+    #[test]
+    fn part2_example() {
+        check(part2, EXAMPLE, 64);
+    }
 }
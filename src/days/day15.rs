@@ -1,6 +1,10 @@
-use std::str::FromStr;
+use crate::parse::{self, Line, ParseError};
+use crate::solution::{Solution, SolveResult};
 
-fn hash(s: &str) -> u8 {
+crate::registry::puzzle!(2023, 15, "Lens Library", part1, part2, |input| parse::errors(parse_steps(input)));
+
+/// The HASH algorithm.
+pub fn hash(s: &str) -> u8 {
     let mut cval: u64 = 0;
     for c in s.chars() {
         cval += c as u64;
@@ -9,34 +13,60 @@ fn hash(s: &str) -> u8 {
     cval as u8
 }
 
-pub(crate) fn part1(input: String) {
-    println!("{}", input.replace('\n', "").split(',').map(|s| hash(s) as u64).sum::<u64>());
+/// Sums the HASH of every step.
+pub fn part1(input: String) -> SolveResult {
+    Ok(Solution::new(steps(&input).map(|(_, s)| hash(s) as u64).sum::<u64>()))
+}
 
+// every comma separated step, with the line it's on
+fn steps(input: &str) -> impl Iterator<Item = (Line<'_>, &str)> {
+    parse::lines(DAY, input).flat_map(|line| line.text.split(',').filter(|s| !s.is_empty()).map(move |s| (line, s)))
+}
+
+/// One step of the initialization sequence.
+pub enum Step<'a> {
+    /// `<label>-`: remove the lens with this label.
+    Remove(&'a str),
+    /// `<label>=<n>`: insert or replace the lens with this label and focal length.
+    Insert(&'a str, u8),
+}
+
+/// Parses the comma separated steps.
+pub fn parse_steps(input: &str) -> Result<Vec<Step<'_>>, ParseError> {
+    steps(input)
+        .map(|(line, s)| {
+            if let Some(label) = s.strip_suffix('-') {
+                Ok(Step::Remove(label))
+            } else {
+                let (label, n) = line.split_once(s, "=")?;
+                Ok(Step::Insert(label, line.parse(n, "focal length")?))
+            }
+        })
+        .collect()
 }
 
 const EMPTY_VEC: Vec<(&str, u8)> = vec![];
 
-pub(crate) fn part2(input: String) {
+/// The total focusing power of the lenses after running every step.
+pub fn part2(input: String) -> SolveResult {
     let mut lenses: [Vec<(&str, u8)>; 256] = [EMPTY_VEC; 256];
-    let input = input.replace('\n', "");
-    let sequence = input.split(',').collect::<Vec<&str>>();
-    for s in sequence {
-        if s.ends_with('-') {
-            let label = &s[..s.len() - 1];
-            let hash = hash(label);
-            let lbox = &mut lenses[hash as usize];
-            if let Some(index) = lbox.iter().position(|(s, _)| *s == label) {
-                lbox.remove(index);
+    for step in parse_steps(&input)? {
+        match step {
+            Step::Remove(label) => {
+                let hash = hash(label);
+                let lbox = &mut lenses[hash as usize];
+                if let Some(index) = lbox.iter().position(|(s, _)| *s == label) {
+                    lbox.remove(index);
+                }
             }
-        } else {
-            let (label, n) = s.split_once('=').unwrap();
-            let hash = hash(label);
-            let lbox = &mut lenses[hash as usize];
-            let n = u8::from_str(n).unwrap();
-            if let Some(index) = lbox.iter().position(|(s, _)| *s == label) {
-                lbox[index].1 = n;
-            } else {
-                lbox.push((label, n));
+            Step::Insert(label, n) => {
+                let hash = hash(label);
+                let lbox = &mut lenses[hash as usize];
+                if let Some(index) = lbox.iter().position(|(s, _)| *s == label) {
+                    lbox[index].1 = n;
+                } else {
+                    lbox.push((label, n));
+                }
             }
         }
     }
@@ -48,5 +78,30 @@ pub(crate) fn part2(input: String) {
         }
     }
 
-    println!("{power}");
+    Ok(Solution::new(power))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::check;
+
+    const EXAMPLE: &str = "\
+rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7
+";
+
+    #[test]
+    fn part1_example() {
+        check(part1, EXAMPLE, 1320);
+    }
+
+    #[test]
+    fn part1_crlf() {
+        check(part1, &EXAMPLE.replace('\n', "\r\n"), 1320);
+    }
+
+    #[test]
+    fn part2_example() {
+        check(part2, EXAMPLE, 145);
+    }
 }
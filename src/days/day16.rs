@@ -1,50 +1,70 @@
 use std::collections::{HashSet, VecDeque};
+use crate::grid::Grid;
+use crate::parse::{self, ParseError};
+use crate::solution::{Solution, SolveResult};
 
+crate::registry::puzzle!(2023, 16, "The Floor Will Be Lava", part1, part2, |input| {
+    parse::line_errors(input, |input| parse_input(input.to_string()))
+});
+
+/// What's on a tile of the contraption.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-enum MirrorType {
+pub enum MirrorType {
+    /// Empty space (`.`).
     Empty,
+    /// A vertical splitter (`|`).
     VerticalSplit,
+    /// A horizontal splitter (`-`).
     HorizontalSplit,
+    /// A `/` mirror.
     RightMirror,
+    /// A `\` mirror.
     LeftMirror,
 }
 
+/// Which way a beam is travelling.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-enum BeamDirection {
+pub enum BeamDirection {
+    /// Up.
     Upwards,
+    /// Down.
     Downwards,
+    /// Left.
     Leftwards,
+    /// Right.
     Rightwards,
 }
 
 use MirrorType as M;
 use BeamDirection as B;
 
-fn push_boundschecked(x: usize, y: usize, ox: isize, oy: isize, w: usize, h: usize,
-                      dir: BeamDirection, q: &mut VecDeque<(usize, usize, BeamDirection)>)
-{
-    let (xi, yi) = (x as isize, y as isize);
-    if xi + ox < 0 || yi + oy < 0 || xi + ox >= w as isize || yi + oy >= h as isize {
-        return;
+// queues the beam moving on to the next tile in `dir`, unless it leaves the grid
+fn push_next(grid: &Grid<MirrorType>, x: usize, y: usize, dir: BeamDirection, q: &mut VecDeque<(usize, usize, BeamDirection)>) {
+    let offset = match dir {
+        B::Upwards => (0, -1),
+        B::Downwards => (0, 1),
+        B::Leftwards => (-1, 0),
+        B::Rightwards => (1, 0),
+    };
+    if let Some((x, y)) = grid.offset((x, y), offset) {
+        q.push_back((x, y, dir));
     }
-
-    q.push_back(((xi + ox) as usize, (yi + oy) as usize, dir));
 }
 
-fn parse_input(input: String) -> Vec<Vec<MirrorType>> {
-    input.lines().map(|l| l.chars().map(|c| match c {
-        '.'  => M::Empty,
-        '|'  => M::VerticalSplit,
-        '-'  => M::HorizontalSplit,
-        '/'  => M::RightMirror,
-        '\\' => M::LeftMirror,
-        _ => panic!("Invalid character in input!"),
-    }).collect::<Vec<_>>()).collect::<Vec<_>>()
+/// Parses the contraption.
+pub fn parse_input(input: String) -> Result<Grid<MirrorType>, ParseError> {
+    Grid::parse(DAY, &input, |c| match c {
+        '.'  => Some(M::Empty),
+        '|'  => Some(M::VerticalSplit),
+        '-'  => Some(M::HorizontalSplit),
+        '/'  => Some(M::RightMirror),
+        '\\' => Some(M::LeftMirror),
+        _ => None,
+    })
 }
 
-fn get_energised_tiles(grid: &[Vec<MirrorType>], x: usize, y: usize, direction: BeamDirection) -> usize {
-    let (w, h) = (grid[0].len(), grid.len());
-
+/// The number of tiles energised by a beam entering at `(x, y)` travelling in `direction`.
+pub fn get_energised_tiles(grid: &Grid<MirrorType>, x: usize, y: usize, direction: BeamDirection) -> usize {
     let mut queue: VecDeque<(usize, usize, BeamDirection)> = VecDeque::new();
     let mut set: HashSet<(usize, usize, BeamDirection)> = HashSet::new();
     queue.push_back((x, y, direction));
@@ -54,41 +74,41 @@ fn get_energised_tiles(grid: &[Vec<MirrorType>], x: usize, y: usize, direction:
             continue;
         }
         match dir {
-            B::Rightwards => match grid[y][x] {
-                M::Empty | M::HorizontalSplit => push_boundschecked(x, y, 1, 0, w, h, B::Rightwards, &mut queue),
+            B::Rightwards => match grid[(x, y)] {
+                M::Empty | M::HorizontalSplit => push_next(grid, x, y, B::Rightwards, &mut queue),
                 M::VerticalSplit => {
-                    push_boundschecked(x, y, 0, 1, w, h, B::Downwards, &mut queue);
-                    push_boundschecked(x, y, 0, -1, w, h, B::Upwards, &mut queue);
+                    push_next(grid, x, y, B::Downwards, &mut queue);
+                    push_next(grid, x, y, B::Upwards, &mut queue);
                 },
-                M::RightMirror => push_boundschecked(x, y, 0, -1, w, h, B::Upwards, &mut queue),
-                M::LeftMirror => push_boundschecked(x, y, 0, 1, w, h, B::Downwards, &mut queue),
+                M::RightMirror => push_next(grid, x, y, B::Upwards, &mut queue),
+                M::LeftMirror => push_next(grid, x, y, B::Downwards, &mut queue),
             },
-            B::Leftwards => match grid[y][x] {
-                M::Empty | M::HorizontalSplit => push_boundschecked(x, y, -1, 0, w, h, B::Leftwards, &mut queue),
+            B::Leftwards => match grid[(x, y)] {
+                M::Empty | M::HorizontalSplit => push_next(grid, x, y, B::Leftwards, &mut queue),
                 M::VerticalSplit => {
-                    push_boundschecked(x, y, 0, 1, w, h, B::Downwards, &mut queue);
-                    push_boundschecked(x, y, 0, -1, w, h, B::Upwards, &mut queue);
+                    push_next(grid, x, y, B::Downwards, &mut queue);
+                    push_next(grid, x, y, B::Upwards, &mut queue);
                 },
-                M::RightMirror => push_boundschecked(x, y, 0, 1, w, h, B::Downwards, &mut queue),
-                M::LeftMirror => push_boundschecked(x, y, 0, -1, w, h, B::Upwards, &mut queue),
+                M::RightMirror => push_next(grid, x, y, B::Downwards, &mut queue),
+                M::LeftMirror => push_next(grid, x, y, B::Upwards, &mut queue),
             },
-            B::Upwards => match grid[y][x] {
-                M::Empty | M::VerticalSplit => push_boundschecked(x, y, 0, -1, w, h, B::Upwards, &mut queue),
+            B::Upwards => match grid[(x, y)] {
+                M::Empty | M::VerticalSplit => push_next(grid, x, y, B::Upwards, &mut queue),
                 M::HorizontalSplit => {
-                    push_boundschecked(x, y, -1, 0, w, h, B::Leftwards, &mut queue);
-                    push_boundschecked(x, y, 1, 0, w, h, B::Rightwards, &mut queue);
+                    push_next(grid, x, y, B::Leftwards, &mut queue);
+                    push_next(grid, x, y, B::Rightwards, &mut queue);
                 },
-                M::RightMirror => push_boundschecked(x, y, 1, 0, w, h, B::Rightwards, &mut queue),
-                M::LeftMirror => push_boundschecked(x, y, -1, 0, w, h, B::Leftwards, &mut queue),
+                M::RightMirror => push_next(grid, x, y, B::Rightwards, &mut queue),
+                M::LeftMirror => push_next(grid, x, y, B::Leftwards, &mut queue),
             },
-            B::Downwards => match grid[y][x] {
-                M::Empty | M::VerticalSplit => push_boundschecked(x, y, 0, 1, w, h, B::Downwards, &mut queue),
+            B::Downwards => match grid[(x, y)] {
+                M::Empty | M::VerticalSplit => push_next(grid, x, y, B::Downwards, &mut queue),
                 M::HorizontalSplit => {
-                    push_boundschecked(x, y, -1, 0, w, h, B::Leftwards, &mut queue);
-                    push_boundschecked(x, y, 1, 0, w, h, B::Rightwards, &mut queue);
+                    push_next(grid, x, y, B::Leftwards, &mut queue);
+                    push_next(grid, x, y, B::Rightwards, &mut queue);
                 },
-                M::RightMirror => push_boundschecked(x, y, -1, 0, w, h, B::Leftwards, &mut queue),
-                M::LeftMirror => push_boundschecked(x, y, 1, 0, w, h, B::Rightwards, &mut queue),
+                M::RightMirror => push_next(grid, x, y, B::Leftwards, &mut queue),
+                M::LeftMirror => push_next(grid, x, y, B::Rightwards, &mut queue),
             }
         }
     }
@@ -97,14 +117,16 @@ fn get_energised_tiles(grid: &[Vec<MirrorType>], x: usize, y: usize, direction:
     s.len()
 }
 
-pub(crate) fn part1(input: String) {
-    let grid = parse_input(input);
-    println!("{}", get_energised_tiles(&grid, 0, 0, B::Rightwards));
+/// The number of tiles energised by a beam entering at the top left going right.
+pub fn part1(input: String) -> SolveResult {
+    let grid = parse_input(input)?;
+    Ok(Solution::new(get_energised_tiles(&grid, 0, 0, B::Rightwards)))
 }
 
-pub(crate) fn part2(input: String) {
-    let grid = parse_input(input);
-    let (w, h) = (grid[0].len(), grid.len());
+/// The most tiles that can be energised by a beam entering from any edge tile.
+pub fn part2(input: String) -> SolveResult {
+    let grid = parse_input(input)?;
+    let (w, h) = (grid.width(), grid.height());
     let mx = (0..w).map(|x| {
         let top = get_energised_tiles(&grid, x, 0, B::Downwards);
         let bottom = get_energised_tiles(&grid, x, h - 1, B::Upwards);
@@ -116,5 +138,34 @@ pub(crate) fn part2(input: String) {
         left.max(right)
     }).max().unwrap();
     let max = mx.max(my);
-    println!("{max}");
+    Ok(Solution::new(max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::check;
+
+    const EXAMPLE: &str = "\
+.|...\\....
+|.-.\\.....
+.....|-...
+........|.
+..........
+.........\\
+..../.\\\\..
+.-.-/..|..
+.|....-|.\\
+..//.|....
+";
+
+    #[test]
+    fn part1_example() {
+        check(part1, EXAMPLE, 46);
+    }
+
+    #[test]
+    fn part2_example() {
+        check(part2, EXAMPLE, 51);
+    }
 }
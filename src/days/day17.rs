@@ -1,20 +1,31 @@
-use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap};
-use std::fmt::{Debug, Display, Formatter};
-use std::rc::Rc;
-use std::str::FromStr;
-use colored::Colorize;
-
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use crate::graph;
+use crate::grid::Grid;
+use crate::parse::{self, ParseError};
+use crate::render::{Frame, Rgb};
+use crate::solution::{Solution, SolveResult};
+
+crate::registry::puzzle!(2023, 17, "Clumsy Crucible", part1, part2, |input| {
+    parse::line_errors(input, |input| parse_input(input.to_string()))
+});
+
+/// Which way the crucible is moving.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-enum Direction {
+pub enum Direction {
+    /// Up.
     Up,
+    /// Down.
     Down,
+    /// Left.
     Left,
+    /// Right.
     Right,
 }
 
 impl Direction {
-    fn backwards(&self) -> Self {
+    /// The opposite direction.
+    pub fn backwards(&self) -> Self {
         match self {
             Direction::Up => Direction::Down,
             Direction::Down => Direction::Up,
@@ -22,55 +33,39 @@ impl Direction {
             Direction::Right => Direction::Left,
         }
     }
-}
 
-impl Display for Direction {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", match self {
+    /// The `(x, y)` offset of one step this way.
+    pub fn offset(&self) -> (isize, isize) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+        }
+    }
+
+    /// An arrow pointing this way.
+    pub fn glyph(&self) -> char {
+        match self {
             Direction::Up => '^',
             Direction::Down => 'v',
             Direction::Left => '<',
             Direction::Right => '>',
-        })
+        }
     }
 }
 
-impl Into<usize> for Direction {
-    fn into(self) -> usize {
-        match self {
-            Direction::Up => 0,
-            Direction::Down => 1,
-            Direction::Left => 2,
-            Direction::Right => 3,
-        }
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.glyph())
     }
 }
 
-#[derive(Clone, Eq, PartialEq)]
-struct State {
+#[derive(Clone, Eq, PartialEq, Hash)]
+struct Crucible {
     pos: (usize, usize),
-    cost: usize,
     direction: Direction,
     straight_line: u8,
-    prev: Option<Rc<State>>,
-}
-
-impl Debug for State {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {}) {}: {} (sl: {})", self.pos.0, self.pos.1, self.direction, self.cost, self.straight_line)
-    }
-}
-
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.cost.cmp(&self.cost).then_with(|| self.pos.cmp(&other.pos))
-    }
-}
-
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
 }
 
 fn can_move(current_direction: Direction, new_direction: Direction, straight_line: u8, ultra: bool) -> bool {
@@ -89,148 +84,107 @@ fn can_move(current_direction: Direction, new_direction: Direction, straight_lin
     }
 }
 
-fn straight_line_restricted_dijkstra(start: (usize, usize), goal: (usize, usize), grid: &[Vec<usize>], ultra: bool) -> Option<(usize, HashMap<(usize, usize), Direction>)> {
-    let (start_x, start_y) = start;
-    let (goal_x, goal_y) = goal;
-    let mut dist = vec![vec![[[usize::MAX; 11]; 4]; grid[0].len()]; grid.len()];
-    let mut prio_queue = BinaryHeap::new();
-    for n in 0..4 {
-        dist[start_y][start_x][n][0] = 0;
-    }
-
-    for dir in [Direction::Up, Direction::Down, Direction::Left, Direction::Right] {
-        prio_queue.push(State { pos: start, cost: 0 , direction: dir, straight_line: 0 , prev: None });
-    }
-
-    while let Some(State { pos: (x, y), cost, direction, straight_line, prev }) = prio_queue.pop() {
-        let dir_idx: usize = direction.into();
-        let state = State {
-            pos: (x, y),
-            cost,
-            direction,
-            straight_line,
-            prev: prev.as_ref().map(|p| Rc::clone(p)),
-        };
-
-        if (x, y) == goal && (!ultra || straight_line >= 4) {
-            let mut m = HashMap::new();
-            let mut p = prev;
-            while let Some(s) = p {
-                m.insert(s.pos, s.direction);
-                p = s.prev.as_ref().map(|p| Rc::clone(p));
+/// The least heat loss from `start` to `goal` for a crucible that can go at most 3 blocks in a straight line
+/// (or, if `ultra`, must go at least 4 and at most 10 before turning or stopping), and the direction it moves in
+/// at each block of the path.
+pub fn straight_line_restricted_dijkstra(start: (usize, usize), goal: (usize, usize), grid: &Grid<usize>, ultra: bool) -> Option<(usize, HashMap<(usize, usize), Direction>)> {
+    let directions = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+    let starts = directions.map(|direction| Crucible { pos: start, direction, straight_line: 0 });
+
+    let (cost, path) = graph::dijkstra(
+        starts,
+        |crucible| directions.into_iter().filter_map(|direction| {
+            if !can_move(crucible.direction, direction, crucible.straight_line, ultra) {
+                return None;
             }
-            m.insert((x, y), direction);
-
-            return Some((cost, m));
-        }
-
-        if cost > dist[y][x][dir_idx][straight_line as usize] {
-            continue;
-        }
-
-        let mut adj = Vec::with_capacity(3);
-
-        let pprev = Rc::new(state);
-
-        if can_move(direction, Direction::Down, straight_line, ultra) && y < grid.len() - 1 {
-            let sl = if direction == Direction::Down {
-                straight_line + 1
-            } else {
-                1
-            };
-            adj.push(State {
-                pos: (x, y + 1),
-                cost: cost + grid[y + 1][x],
-                direction: Direction::Down,
-                straight_line: sl,
-                prev: Some(Rc::clone(&pprev)),
-            });
-        }
-        if can_move(direction, Direction::Up, straight_line, ultra) && y > 0 {
-            let sl = if direction == Direction::Up {
-                straight_line + 1
-            } else {
-                1
-            };
-            adj.push(State {
-                pos: (x, y - 1),
-                cost: cost + grid[y - 1][x],
-                direction: Direction::Up,
-                straight_line: sl,
-                prev: Some(Rc::clone(&pprev)),
-            });
-        }
-        if can_move(direction, Direction::Right, straight_line, ultra) && x < grid[0].len() - 1 {
-            let sl = if direction == Direction::Right {
-                straight_line + 1
-            } else {
-                1
-            };
-            adj.push(State {
-                pos: (x + 1, y),
-                cost: cost + grid[y][x + 1],
-                direction: Direction::Right,
-                straight_line: sl,
-                prev: Some(Rc::clone(&pprev)),
-            })
-        }
-        if can_move(direction, Direction::Left, straight_line, ultra) && x > 0 {
-            let sl = if direction == Direction::Left {
-                straight_line + 1
+            let pos = grid.offset(crucible.pos, direction.offset())?;
+            let straight_line = if direction == crucible.direction {
+                crucible.straight_line + 1
             } else {
                 1
             };
-            adj.push(State {
-                pos: (x - 1, y),
-                cost: cost + grid[y][x - 1],
-                direction: Direction::Left,
-                straight_line: sl,
-                prev: Some(pprev),
-            })
-        }
+            Some((Crucible { pos, direction, straight_line }, grid[pos]))
+        }).collect::<Vec<_>>(),
+        |crucible| crucible.pos == goal && (!ultra || crucible.straight_line >= 4),
+    )?;
 
-        for state in adj {
-            let (x, y) = state.pos;
-            let dir: usize = state.direction.into();
-            let sl = state.straight_line as usize;
-            let cost = state.cost;
-            if cost < dist[y][x][dir][sl] {
-                prio_queue.push(state);
-                dist[y][x][dir][sl] = cost;
-            }
-        }
-    }
+    Some((cost, path.into_iter().map(|crucible| (crucible.pos, crucible.direction)).collect()))
+}
 
-    None
+/// Parses the map of heat loss digits.
+pub fn parse_input(input: String) -> Result<Grid<usize>, ParseError> {
+    Grid::parse(DAY, &input, |c| c.to_digit(10).map(|d| d as usize))
 }
 
-fn day17(input: String, part2: bool) {
-    let grid = input
-        .lines()
-        .map(|l| l.chars().map(|c| usize::from_str(&c.to_string()).unwrap()).collect::<Vec<_>>())
-        .collect::<Vec<_>>();
-    let (path_length, path) = straight_line_restricted_dijkstra((0, 0), (grid[0].len() - 1, grid.len() - 1), &grid, part2).unwrap();
-    for y in 0..grid.len() {
-        for x in 0..grid[0].len() {
-            if x == 0 && y == 0 {
-                print!("{}", "•".bright_white());
-            } else if let Some(dir) = path.get(&(x, y)) {
-                print!("{}", dir.to_string().bright_white());
-            } else {
-                let n = grid[y][x] as u8;
-                print!("{}", n.to_string().truecolor(255 - n * 28, 0, n * 28));
-            }
+fn day17(input: String, part2: bool) -> SolveResult {
+    let grid = parse_input(input)?;
+    let (path_length, path) = straight_line_restricted_dijkstra((0, 0), (grid.width() - 1, grid.height() - 1), &grid, part2).unwrap();
+    let mut frame = Frame::new(grid.width(), grid.height());
+    for ((x, y), &n) in grid.iter() {
+        if x == 0 && y == 0 {
+            frame.set(x, y, '•', Some(Rgb::WHITE));
+        } else if let Some(dir) = path.get(&(x, y)) {
+            frame.set(x, y, dir.glyph(), Some(Rgb::WHITE));
+        } else {
+            let n = n as u8;
+            frame.set(x, y, char::from(b'0' + n), Some(Rgb(255 - n * 28, 0, n * 28)));
         }
-        println!();
     }
 
-    println!("\n{path_length}");
+    Ok(Solution::new(path_length).with_frame(frame))
 }
 
-pub(crate) fn part1(input: String) {
-    day17(input, false);
+/// The least heat loss from the top left to the bottom right block with a normal crucible.
+pub fn part1(input: String) -> SolveResult {
+    day17(input, false)
 }
 
-pub(crate) fn part2(input: String) {
-    day17(input, true);
+/// Like [`part1`], but with an ultra crucible.
+pub fn part2(input: String) -> SolveResult {
+    day17(input, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::check;
+
+    const EXAMPLE_1: &str = "\
+2413432311323
+3215453535623
+3255245654254
+3446585845452
+4546657867536
+1438598798454
+4457876987766
+3637877979653
+4654967986887
+4564679986453
+1224686865563
+2546548887735
+4322674655533
+";
+
+    const EXAMPLE_2: &str = "\
+111111111111
+999999999991
+999999999991
+999999999991
+999999999991
+";
+
+    #[test]
+    fn part1_example() {
+        check(part1, EXAMPLE_1, 102);
+    }
+
+    #[test]
+    fn part2_example_1() {
+        check(part2, EXAMPLE_1, 94);
+    }
+
+    #[test]
+    fn part2_example_2() {
+        check(part2, EXAMPLE_2, 71);
+    }
 }
@@ -1,14 +1,30 @@
 use std::str::FromStr;
+use crate::parse::{self, ParseError};
+use crate::solution::{Solution, SolveResult};
 
-enum Direction {
+crate::registry::puzzle!(2023, 18, "Lavaduct Lagoon", part1, part2, |input| {
+    // the plan is read differently in part 2, from the colours
+    parse::line_errors(input, |input| {
+        parse_input(input.to_string(), false)?;
+        parse_input(input.to_string(), true)
+    })
+});
+
+/// Which way the digger moves.
+pub enum Direction {
+    /// Up.
     Up,
+    /// Down.
     Down,
+    /// Left.
     Left,
+    /// Right.
     Right,
 }
 
 impl Direction {
-    fn move_in(&self, c: (i64, i64), n: i64) -> (i64, i64) {
+    /// Moves `n` steps from `c` in this direction.
+    pub fn move_in(&self, c: (i64, i64), n: i64) -> (i64, i64) {
         let (x, y) = c;
         match self {
             Direction::Up => (x, y - n),
@@ -18,7 +34,8 @@ impl Direction {
         }
     }
 
-    fn from_i64(n: i64) -> Option<Self> {
+    /// The direction encoded in the last hex digit of a colour (0 is right, then clockwise).
+    pub fn from_i64(n: i64) -> Option<Self> {
         match n {
             0 => Some(Direction::Right),
             1 => Some(Direction::Down),
@@ -43,24 +60,32 @@ impl FromStr for Direction {
     }
 }
 
-fn parse_input(input: String, part2: bool) -> Vec<(Direction, i64)> {
-    input.lines().map(|line| {
-        let v = line.split_whitespace().collect::<Vec<_>>();
+/// Parses the dig plan into (direction, distance) steps; with `part2` they're decoded from the colours.
+pub fn parse_input(input: String, part2: bool) -> Result<Vec<(Direction, i64)>, ParseError> {
+    parse::lines(DAY, &input).map(|line| {
+        let v = line.text.split_whitespace().collect::<Vec<_>>();
+        if v.len() != 3 {
+            return Err(line.error(line.text, "expected a direction, a distance and a colour"));
+        }
 
         if !part2 {
-            let d = Direction::from_str(v[0]).unwrap();
-            let n = i64::from_str(v[1]).unwrap();
-            (d, n)
+            let d = Direction::from_str(v[0]).map_err(|_| line.error(v[0], "invalid direction"))?;
+            let n = line.parse(v[1], "distance")?;
+            Ok((d, n))
         } else {
-            let hex = v[2].chars().filter(char::is_ascii_alphanumeric).collect::<String>();
-            let c = i64::from_str_radix(&hex, 16).unwrap();
-            let d = Direction::from_i64(c & 0xF).unwrap();
-            (d, c >> 4)
+            let hex = v[2]
+                .strip_prefix("(#")
+                .and_then(|s| s.strip_suffix(')'))
+                .ok_or_else(|| line.error(v[2], "expected a colour like (#70c710)"))?;
+            let c = i64::from_str_radix(hex, 16).map_err(|_| line.error(hex, "invalid hex number"))?;
+            let d = Direction::from_i64(c & 0xF).ok_or_else(|| line.error(&hex[hex.len() - 1..], "invalid direction"))?;
+            Ok((d, c >> 4))
         }
     }).collect()
 }
 
-fn shoelace(points: &[(i64, i64)]) -> i64 {
+/// The area of a closed polygon (whose first and last points are the same) by the shoelace formula.
+pub fn shoelace(points: &[(i64, i64)]) -> i64 {
     points.windows(2).map(|s| {
         let (x1, y1) = s[0];
         let (x2, y2) = s[1];
@@ -68,12 +93,14 @@ fn shoelace(points: &[(i64, i64)]) -> i64 {
     }).sum::<i64>().abs() / 2
 }
 
-fn pick(points: &[(i64, i64)], b: i64) -> i64 {
+/// The number of integer points inside or on the boundary of a polygon with `b` boundary points, by Pick's
+/// theorem.
+pub fn pick(points: &[(i64, i64)], b: i64) -> i64 {
     shoelace(points) + 1 + b / 2
 }
 
-fn day18(input: String, part2: bool) {
-    let v = parse_input(input, part2);
+fn day18(input: String, part2: bool) -> SolveResult {
+    let v = parse_input(input, part2)?;
     let mut b = 0;
     let mut p = (0, 0);
     let mut points = vec![];
@@ -83,13 +110,48 @@ fn day18(input: String, part2: bool) {
         points.push(p);
     }
     let a = pick(&points, b);
-    println!("{a}");
+    Ok(Solution::new(a))
 }
 
-pub(crate) fn part1(input: String) {
-    day18(input, false);
+/// How many cubic metres of lava the lagoon holds.
+pub fn part1(input: String) -> SolveResult {
+    day18(input, false)
 }
 
-pub(crate) fn part2(input: String) {
-    day18(input, true);
+/// Like [`part1`], but with the real instructions hidden in the colours.
+pub fn part2(input: String) -> SolveResult {
+    day18(input, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::check;
+
+    const EXAMPLE: &str = "\
+R 6 (#70c710)
+D 5 (#0dc571)
+L 2 (#5713f0)
+D 2 (#d2c081)
+R 2 (#59c680)
+D 2 (#411b91)
+L 5 (#8ceee2)
+U 2 (#caa173)
+L 1 (#1b58a2)
+U 2 (#caa171)
+R 2 (#7807d2)
+U 3 (#a77fa3)
+L 2 (#015232)
+U 2 (#7a21e3)
+";
+
+    #[test]
+    fn part1_example() {
+        check(part1, EXAMPLE, 62);
+    }
+
+    #[test]
+    fn part2_example() {
+        check(part2, EXAMPLE, 952408144115_i64);
+    }
 }
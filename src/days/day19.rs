@@ -1,18 +1,29 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use std::str::FromStr;
 use regex::Regex;
+use crate::graph::{self, Graph};
+use crate::parse::{self, ParseError};
+use crate::solution::{Solution, SolveResult};
 
+crate::registry::puzzle!(2023, 19, "Aplenty", part1, part2, |input| parse::errors(parse_input(input.to_string())));
+
+/// A machine part and its four ratings.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-struct Part {
-    x: i64,
-    m: i64,
-    a: i64,
-    s: i64,
+pub struct Part {
+    /// Extremely cool looking.
+    pub x: i64,
+    /// Musical.
+    pub m: i64,
+    /// Aerodynamic.
+    pub a: i64,
+    /// Shiny.
+    pub s: i64,
 }
 
 impl Part {
-    fn get_rating_by_type(&self, rtype: RatingType) -> i64 {
+    /// One of the part's ratings.
+    pub fn get_rating_by_type(&self, rtype: RatingType) -> i64 {
         match rtype {
             RatingType::X => self.x,
             RatingType::M => self.m,
@@ -21,7 +32,8 @@ impl Part {
         }
     }
 
-    fn get_rating_mut_by_type(&mut self, rtype: RatingType) -> &mut i64 {
+    /// One of the part's ratings, mutably.
+    pub fn get_rating_mut_by_type(&mut self, rtype: RatingType) -> &mut i64 {
         match rtype {
             RatingType::X => &mut self.x,
             RatingType::M => &mut self.m,
@@ -31,14 +43,18 @@ impl Part {
     }
 }
 
+/// The comparison in a conditional rule.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-enum Comparator {
+pub enum Comparator {
+    /// `<`
     LessThan,
+    /// `>`
     GreaterThan,
 }
 
 impl Comparator {
-    fn apply(&self, rtype: RatingType, part: Part, n: i64) -> bool {
+    /// Whether the part's rating compares to `n`.
+    pub fn apply(&self, rtype: RatingType, part: Part, n: i64) -> bool {
         match self {
             Comparator::LessThan => part.get_rating_by_type(rtype) < n,
             Comparator::GreaterThan => part.get_rating_by_type(rtype) > n,
@@ -46,11 +62,16 @@ impl Comparator {
     }
 }
 
+/// Which of a part's ratings a rule looks at.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-enum RatingType {
+pub enum RatingType {
+    /// `x`
     X,
+    /// `m`
     M,
+    /// `a`
     A,
+    /// `s`
     S,
 }
 
@@ -68,14 +89,18 @@ impl FromStr for RatingType {
     }
 }
 
+/// One rule of a workflow; both kinds end with where to send the part (a workflow name, `A` or `R`).
 #[derive(Debug, Clone)]
-enum Rule {
+pub enum Rule {
+    /// `<rating><comparator><n>:<target>`
     Conditional(RatingType, Comparator, i64, String),
+    /// Just `<target>`.
     Unconditional(String),
 }
 
 impl Rule {
-    fn opposite(&self) -> Self {
+    /// The rule whose condition holds exactly when this one's doesn't.
+    pub fn opposite(&self) -> Self {
         match self {
             Rule::Unconditional(s) => Rule::Unconditional(s.clone()),
             Rule::Conditional(rtype, Comparator::GreaterThan, n, s) => Rule::Conditional(*rtype, Comparator::LessThan, *n + 1, s.clone()),
@@ -84,15 +109,21 @@ impl Rule {
     }
 }
 
+/// A named list of rules, tried in order.
 #[derive(Debug, Clone)]
-struct Workflow {
-    rules: Vec<Rule>,
+pub struct Workflow {
+    /// The rules.
+    pub rules: Vec<Rule>,
 }
 
+/// What a workflow does with a part.
 #[derive(Debug, Clone, PartialEq)]
-enum WorkflowResult {
+pub enum WorkflowResult {
+    /// The part is accepted (`A`).
     Accept,
+    /// The part is rejected (`R`).
     Reject,
+    /// The part is sent to another workflow.
     Jump(String),
 }
 
@@ -109,7 +140,8 @@ impl FromStr for WorkflowResult {
 }
 
 impl Workflow {
-    fn run(&self, part: Part) -> WorkflowResult {
+    /// Runs a part through the workflow.
+    pub fn run(&self, part: Part) -> WorkflowResult {
         for rule in &self.rules {
             match rule {
                 Rule::Unconditional(jump_to) => return WorkflowResult::from_str(jump_to).unwrap(),
@@ -119,11 +151,13 @@ impl Workflow {
             }
         }
 
-        panic!("Workflow ended without accepting or rejecting!");
+        unreachable!("parse_input makes sure a workflow's last rule is unconditional");
     }
 }
 
-fn run_workflows(workflows: &HashMap<String, Workflow>, part: Part) -> i64 {
+/// Runs a part through the workflows starting at `in`, returning the sum of its ratings if it's accepted or 0 if
+/// it's rejected.
+pub fn run_workflows(workflows: &HashMap<String, Workflow>, part: Part) -> i64 {
     let mut result = WorkflowResult::Jump("in".to_string());
     while let WorkflowResult::Jump(s) = &result {
         let workflow = workflows.get(s).unwrap();
@@ -137,52 +171,103 @@ fn run_workflows(workflows: &HashMap<String, Workflow>, part: Part) -> i64 {
     }
 }
 
-fn parse_input(input: String) -> (HashMap<String, Workflow>, Vec<Part>) {
-    let regex = Regex::new(r"^(?P<id>.*?)\{(?P<rules>.*?)}$").unwrap();
-    let (workflows, parts) = input.split_once("\n\n").unwrap();
+/// Parses the workflows (by name) and the parts.
+pub fn parse_input(input: String) -> Result<(HashMap<String, Workflow>, Vec<Part>), ParseError> {
+    let regex = Regex::new(r"^(?P<id>[a-z]+)\{(?P<rules>.*?)}$").unwrap();
+    let sections = parse::sections(DAY, &input);
+    let [workflows, parts] = &sections[..] else {
+        return Err(ParseError::whole_input(DAY, "expected workflows and parts separated by a blank line"));
+    };
 
     let mut workflows_map = HashMap::new();
-    for line in workflows.lines() {
-        let workflows_caps = regex.captures(line).unwrap();
-        let id = &workflows_caps["id"];
-        let rules = &workflows_caps["rules"];
+    let mut targets = vec![];
+    for line in workflows {
+        let workflows_caps = regex.captures(line.text).ok_or_else(|| line.error(line.text, "expected a workflow like px{a<2006:qkq,rfg}"))?;
+        let id = workflows_caps.name("id").unwrap().as_str();
+        let rules = workflows_caps.name("rules").unwrap().as_str();
 
         let mut rv = vec![];
         for rule in rules.split(",") {
             if !rule.contains(":") {
+                targets.push((*line, id, rule));
                 rv.push(Rule::Unconditional(rule.to_string()));
             } else {
-                let (c, t) = rule.split_once(":").unwrap();
-                let rtype = RatingType::from_str(&c[0..1]).unwrap();
-                let condtype = if c.contains(">") { Comparator::GreaterThan } else { Comparator::LessThan };
-                let n = i64::from_str(&c.chars().filter(char::is_ascii_digit).collect::<String>()).unwrap();
+                let (c, t) = line.split_once(rule, ":")?;
+                let rtype = RatingType::from_str(c.get(0..1).unwrap_or(c)).map_err(|_| line.error(c, "invalid rating (expected x, m, a or s)"))?;
+                let condtype = match c.get(1..2) {
+                    Some(">") => Comparator::GreaterThan,
+                    Some("<") => Comparator::LessThan,
+                    _ => return Err(line.error(c.get(1..).unwrap_or(c), "expected `<` or `>`")),
+                };
+                let n = line.parse(&c[2..], "rating")?;
+                targets.push((*line, id, t));
                 rv.push(Rule::Conditional(rtype, condtype, n, t.to_string()));
             }
         }
+        // otherwise a part that matches none of the conditions has nowhere to go
+        if let Some(Rule::Conditional(..)) = rv.last() {
+            let last = rules.rsplit(',').next().unwrap();
+            return Err(line.error(last, "the last rule must be just where to send the part"));
+        }
 
         workflows_map.insert(id.to_string(), Workflow {
             rules: rv,
         });
     }
 
+    if !workflows_map.contains_key("in") {
+        return Err(ParseError::whole_input(DAY, "there is no `in` workflow to start from"));
+    }
+    let mut wiring = Graph::new();
+    for &(line, source, target) in &targets {
+        if matches!(target, "A" | "R") {
+            continue;
+        }
+        if !workflows_map.contains_key(target) {
+            return Err(line.error(target, "unknown workflow"));
+        }
+        let (source, target) = (wiring.add_node(source), wiring.add_node(target));
+        wiring.add_edge(source, target, ());
+    }
+
+    // a rule that stays within a strongly connected group of workflows can send a part round them forever
+    if let Some(start) = wiring.id("in") {
+        let reachable = graph::dfs(start, |&id| wiring.edges(id).iter().map(|&(next, _)| next).collect::<Vec<_>>())
+            .into_iter()
+            .collect::<HashSet<_>>();
+        let mut component = HashMap::new();
+        for (i, members) in wiring.strongly_connected_components().into_iter().enumerate() {
+            component.extend(members.into_iter().map(|id| (id, i)));
+        }
+        for &(line, source, target) in &targets {
+            let (Some(from), Some(to)) = (wiring.id(source), wiring.id(target)) else {
+                continue;
+            };
+            if reachable.contains(&from) && component[&from] == component[&to] {
+                return Err(line.error(target, "this sends parts round in a loop of workflows"));
+            }
+        }
+    }
+
     let regex = Regex::new(r"^\{x=(?P<x>\d+),m=(?P<m>\d+),a=(?P<a>\d+),s=(?P<s>\d+)}$").unwrap();
     let mut parts_v = vec![];
-    for line in parts.lines() {
-        let part_caps = regex.captures(line).unwrap();
-        let x = i64::from_str(&part_caps["x"]).unwrap();
-        let m = i64::from_str(&part_caps["m"]).unwrap();
-        let a = i64::from_str(&part_caps["a"]).unwrap();
-        let s = i64::from_str(&part_caps["s"]).unwrap();
+    for line in parts {
+        let part_caps = regex.captures(line.text).ok_or_else(|| line.error(line.text, "expected a part like {x=787,m=2655,a=1222,s=2876}"))?;
+        let rating = |name| line.parse(part_caps.name(name).unwrap().as_str(), "rating");
+        let x = rating("x")?;
+        let m = rating("m")?;
+        let a = rating("a")?;
+        let s = rating("s")?;
         parts_v.push(Part { x, m, a, s });
-
     }
 
-    (workflows_map, parts_v)
+    Ok((workflows_map, parts_v))
 }
 
-pub(crate) fn part1(input: String) {
-    let (workflows, parts) = parse_input(input);
-    println!("{}", parts.into_iter().map(|p| run_workflows(&workflows, p)).sum::<i64>());
+/// Sums the ratings of every accepted part.
+pub fn part1(input: String) -> SolveResult {
+    let (workflows, parts) = parse_input(input)?;
+    Ok(Solution::new(parts.into_iter().map(|p| run_workflows(&workflows, p)).sum::<i64>()))
 }
 
 #[derive(Debug, Clone)]
@@ -238,8 +323,9 @@ fn build_tree(workflows: &HashMap<String, Workflow>, start: &str, mut prev: Opti
     v
 }
 
-pub(crate) fn part2(input: String) {
-    let (workflows, _) = parse_input(input);
+/// The number of combinations of ratings from 1 to 4000 that would be accepted.
+pub fn part2(input: String) -> SolveResult {
+    let (workflows, _) = parse_input(input)?;
     let n = build_tree(&workflows, "in", None).into_iter().map(|end| {
         let mut mins = Part { x: 0, m: 0, a: 0, s: 0 };
         let mut maxs = Part { x: 4001, m: 4001, a: 4001, s: 4001 };
@@ -266,5 +352,56 @@ pub(crate) fn part2(input: String) {
 
         dx * dm * da * ds
     }).sum::<i64>();
-    println!("{n}");
+    Ok(Solution::new(n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::check;
+
+    const EXAMPLE: &str = "\
+px{a<2006:qkq,m>2090:A,rfg}
+pv{a>1716:R,A}
+lnx{m>1548:A,A}
+rfg{s<537:gd,x>2440:R,A}
+qs{s>3448:A,lnx}
+qkq{x<1416:A,crn}
+crn{x>2662:A,R}
+in{s<1351:px,qqz}
+qqz{s>2770:qs,m<1801:hdj,R}
+gd{a>3333:R,R}
+hdj{m>838:A,pv}
+
+{x=787,m=2655,a=1222,s=2876}
+{x=1679,m=44,a=2067,s=496}
+{x=2036,m=264,a=79,s=2244}
+{x=2461,m=1339,a=466,s=291}
+{x=2127,m=1623,a=2188,s=1013}
+";
+
+    #[test]
+    fn part1_example() {
+        check(part1, EXAMPLE, 19114);
+    }
+
+    #[test]
+    fn part2_example() {
+        check(part2, EXAMPLE, 167409079868000_i64);
+    }
+
+    #[test]
+    fn workflows_must_send_every_part_somewhere() {
+        let error = |input: &str| parse_input(input.to_string()).err().map(|e| e.to_string());
+        assert_eq!(
+            error("in{x<10:A,m>5:R}\n\n{x=1,m=2,a=3,s=4}\n"),
+            Some("day 19, line 1, column 11: the last rule must be just where to send the part (found `m>5:R`)".to_string()),
+        );
+        assert_eq!(
+            error("in{x<10:ab,R}\nab{m>5:A,cd}\ncd{ab}\nxy{xy}\n\n{x=1,m=2,a=3,s=4}\n"),
+            Some("day 19, line 2, column 10: this sends parts round in a loop of workflows (found `cd`)".to_string()),
+        );
+        // a loop that can't be reached from `in` never gets a part
+        assert!(error("in{A}\nxy{xy}\n\n{x=1,m=2,a=3,s=4}\n").is_none());
+    }
 }
@@ -1,49 +1,381 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
+use crate::parse::{self, Line, ParseError};
+use crate::solution::{Solution, SolveResult};
 
-fn day2(input: String, part2: bool) {
-    println!("{}", input.lines().map(|line| {
-        let (id, cubes) = line.split_once(":").unwrap();
-        let id = i32::from_str(&id.replace("Game ", "")).unwrap();
-
-        let mut iter = cubes.trim().split(";").map(|s| {
-            let turn = s.trim();
-            let mut red = 0;
-            let mut green = 0;
-            let mut blue = 0;
-
-            for c in turn.split(", ") {
-                let (n, colour) = c.split_once(" ").unwrap();
-                let n = i32::from_str(n).unwrap();
-                match colour {
-                    "red" => red += n,
-                    "green" => green += n,
-                    "blue" => blue += n,
-                    _ => panic!("illegal colour {colour}"),
-                }
-            }
+crate::registry::puzzle!(2023, 2, "Cube Conundrum", part1, part2, |input| {
+    parse::line_errors(input, parse_games)
+});
 
-            (red, green, blue)
-        });
+/// The bag part 1 asks about.
+pub const DEFAULT_BAG: &str = "12 red, 13 green, 14 blue";
+
+/// Some cubes, counted by colour. Colours can be called anything.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Cubes(pub BTreeMap<String, u32>);
+
+impl Cubes {
+    /// How many cubes of `colour` there are.
+    pub fn count(&self, colour: &str) -> u32 {
+        self.0.get(colour).copied().unwrap_or(0)
+    }
+
+    /// Whether there are at least as many of every colour as `other` has.
+    pub fn contains(&self, other: &Cubes) -> bool {
+        other.0.iter().all(|(colour, &n)| self.count(colour) >= n)
+    }
+
+    /// The most of each colour in either.
+    pub fn union(&self, other: &Cubes) -> Cubes {
+        let mut union = self.clone();
+        for (colour, &n) in &other.0 {
+            let count = union.0.entry(colour.clone()).or_default();
+            *count = (*count).max(n);
+        }
+        union
+    }
+
+    /// The product of the counts of `colours` (a colour that isn't here counts 0).
+    pub fn power<'a>(&self, colours: impl IntoIterator<Item = &'a str>) -> u64 {
+        colours.into_iter().map(|colour| u64::from(self.count(colour))).product()
+    }
+}
+
+// `<n> <colour>`
+fn parse_count(s: &str) -> Option<(u32, &str)> {
+    let (n, colour) = s.trim().split_once(' ')?;
+    let colour = colour.trim();
+    Some((u32::from_str(n).ok()?, colour)).filter(|_| !colour.is_empty())
+}
+
+impl FromStr for Cubes {
+    type Err = String;
+
+    /// `12 red, 13 green, 14 blue`, where the counts can also go on separate lines (as in a bag file).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cubes = Cubes::default();
+        for count in s.split([',', '\n']).map(str::trim).filter(|s| !s.is_empty()) {
+            let (n, colour) = parse_count(count).ok_or_else(|| format!("expected `<count> <colour>`, got `{count}`"))?;
+            *cubes.0.entry(colour.to_string()).or_default() += n;
+        }
+        Ok(cubes)
+    }
+}
+
+impl Display for Cubes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let counts = self.0.iter().map(|(colour, n)| format!("{n} {colour}")).collect::<Vec<_>>();
+        write!(f, "{}", counts.join(", "))
+    }
+}
+
+/// One game: its id, and the cubes shown in each round.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Game {
+    /// The game's id.
+    pub id: u32,
+    /// What was shown each round.
+    pub rounds: Vec<Cubes>,
+}
+
+impl Game {
+    /// Whether every round could have come out of `bag`.
+    pub fn is_possible(&self, bag: &Cubes) -> bool {
+        self.rounds.iter().all(|round| bag.contains(round))
+    }
+
+    /// The smallest bag every round could have come out of.
+    pub fn minimal_bag(&self) -> Cubes {
+        self.rounds.iter().fold(Cubes::default(), |bag, round| bag.union(round))
+    }
+}
+
+/// Parses a `Game <id>: <cubes>; <cubes>; ...` line, where `<cubes>` is like `3 blue, 4 red`.
+pub fn parse_game(line: Line) -> Result<Game, ParseError> {
+    let (id, cubes) = line.split_once(line.text, ":")?;
+    let id = line.parse(line.strip_prefix(id, "Game ")?, "game id")?;
+
+    let rounds = cubes.split(';').map(|round| {
+        let mut cubes = Cubes::default();
+        for count in round.split(',') {
+            let (n, colour) = parse_count(count)
+                .ok_or_else(|| line.error(count.trim(), "expected `<count> <colour>`"))?;
+            *cubes.0.entry(colour.to_string()).or_default() += n;
+        }
+        Ok(cubes)
+    }).collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Game { id, rounds })
+}
+
+/// Parses every game in the input.
+pub fn parse_games(input: &str) -> Result<Vec<Game>, ParseError> {
+    parse::lines(DAY, input).map(parse_game).collect()
+}
+
+/// [`DEFAULT_BAG`], parsed.
+pub fn default_bag() -> Cubes {
+    Cubes::from_str(DEFAULT_BAG).expect("the default bag parses")
+}
+
+/// Sums the ids of the games that are possible with `bag`.
+pub fn sum_possible(games: &[Game], bag: &Cubes) -> u64 {
+    games.iter().filter(|game| game.is_possible(bag)).map(|game| u64::from(game.id)).sum()
+}
+
+/// Sums the powers of each game's minimal bag: the product of its counts of the bag's colours and of any others the
+/// game shows.
+pub fn sum_powers(games: &[Game], bag: &Cubes) -> u64 {
+    reports(games, bag).iter().map(|report| report.power).sum()
+}
+
+/// What one game needs of a bag.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameReport {
+    /// The game's id.
+    pub id: u32,
+    /// Whether the bag could have played it.
+    pub possible: bool,
+    /// The smallest bag that could have.
+    pub minimal_bag: Cubes,
+    /// The product of the minimal bag's counts (see [`sum_powers`]).
+    pub power: u64,
+    /// The colour the game needs the biggest share of the bag's cubes of, which is the first to rule it out as the
+    /// bag shrinks. `None` if the game shows no cubes.
+    pub limiting_colour: Option<String>,
+}
 
-        if part2 {
-            let reds = iter.clone().map(|(r, _, _)| r).max().unwrap();
-            let greens = iter.clone().map(|(_, g, _)| g).max().unwrap();
-            let blues = iter.map(|(_, _, b)| b).max().unwrap();
-            reds * greens * blues
-        } else {
-            if iter.all(|(r, g, b)| r <= 12 && g <= 13 && b <= 14) {
-                id
-            } else {
-                0
+// the colour `minimal` needs the biggest share of the bag's cubes of (all of a colour the bag doesn't have is
+// infinitely much); the first alphabetically on a tie
+fn limiting_colour(minimal: &Cubes, bag: &Cubes) -> Option<String> {
+    let share = |colour: &str| (minimal.count(colour), bag.count(colour));
+    let more = |(need_a, have_a): (u32, u32), (need_b, have_b): (u32, u32)| {
+        u64::from(need_a) * u64::from(have_b) > u64::from(need_b) * u64::from(have_a)
+    };
+    minimal.0.iter().filter(|(_, &n)| n > 0).map(|(colour, _)| colour).fold(None, |limiting: Option<&String>, colour| {
+        match limiting {
+            Some(limiting) if !more(share(colour), share(limiting)) => Some(limiting),
+            _ => Some(colour),
+        }
+    }).cloned()
+}
+
+/// Reports on every game against `bag`.
+pub fn reports(games: &[Game], bag: &Cubes) -> Vec<GameReport> {
+    games.iter().map(|game| {
+        let minimal_bag = game.minimal_bag();
+        GameReport {
+            id: game.id,
+            possible: game.is_possible(bag),
+            power: minimal_bag.power(bag.union(&minimal_bag).0.keys().map(String::as_str)),
+            limiting_colour: limiting_colour(&minimal_bag, bag),
+            minimal_bag,
+        }
+    }).collect()
+}
+
+/// The ids of the games that are possible with `bag`.
+pub fn possible_games(games: &[Game], bag: &Cubes) -> Vec<u32> {
+    games.iter().filter(|game| game.is_possible(bag)).map(|game| game.id).collect()
+}
+
+/// The bag with the fewest cubes that makes at least `k` games possible, or `None` if there aren't `k` games.
+///
+/// Each colour's count only needs to be one some game needs, so this tries every combination of those for all but
+/// the last colour and takes the `k`th smallest need of the games that fit for the last. That's fast for a few
+/// colours, but grows with the number of games to the power of the number of colours.
+pub fn smallest_bag_for(games: &[Game], k: usize) -> Option<Cubes> {
+    if k > games.len() {
+        return None;
+    }
+    let minimal = games.iter().map(Game::minimal_bag).collect::<Vec<_>>();
+    let colours = minimal.iter().flat_map(|bag| bag.0.keys().cloned()).collect::<BTreeSet<_>>();
+    let colours = colours.into_iter().collect::<Vec<_>>();
+
+    // the best bag with the counts of `colours[..fixed.len()]` fixed, given the games that fit them so far
+    fn search(colours: &[String], minimal: &[&Cubes], k: usize, fixed: &mut Vec<u32>, best: &mut Option<(u64, Cubes)>) {
+        let Some((colour, rest)) = colours[fixed.len()..].split_first() else {
+            return;
+        };
+        if rest.is_empty() {
+            let mut needs = minimal.iter().map(|bag| bag.count(colour)).collect::<Vec<_>>();
+            needs.sort_unstable();
+            let total = fixed.iter().map(|&n| u64::from(n)).sum::<u64>() + u64::from(needs[k - 1]);
+            if best.as_ref().is_none_or(|(best, _)| total < *best) {
+                let counts = fixed.iter().copied().chain([needs[k - 1]]);
+                *best = Some((total, Cubes(colours.iter().cloned().zip(counts).filter(|&(_, n)| n > 0).collect())));
             }
+            return;
         }
-    }).sum::<i32>());
+
+        let mut candidates = minimal.iter().map(|bag| bag.count(colour)).collect::<Vec<_>>();
+        candidates.sort_unstable();
+        candidates.dedup();
+        for n in candidates {
+            let fitting = minimal.iter().copied().filter(|bag| bag.count(colour) <= n).collect::<Vec<_>>();
+            if fitting.len() >= k {
+                fixed.push(n);
+                search(colours, &fitting, k, fixed, best);
+                fixed.pop();
+            }
+        }
+    }
+
+    if k == 0 || colours.is_empty() {
+        return Some(Cubes::default());
+    }
+    let mut best = None;
+    search(&colours, &minimal.iter().collect::<Vec<_>>(), k, &mut vec![], &mut best);
+    best.map(|(_, bag)| bag)
+}
+
+// a CSV field, quoted if it needs to be
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// The reports as CSV, with a header: `id,possible,<a column per colour>,power,limiting_colour`. The colours are
+/// those of `bag` and of every minimal bag, in alphabetical order.
+pub fn reports_to_csv(reports: &[GameReport], bag: &Cubes) -> String {
+    let colours = bag.0.keys()
+        .chain(reports.iter().flat_map(|r| r.minimal_bag.0.keys()))
+        .collect::<BTreeSet<_>>();
+    let mut csv = ["id", "possible"].into_iter().map(str::to_string)
+        .chain(colours.iter().map(|c| csv_field(c)))
+        .chain(["power".to_string(), "limiting_colour".to_string()])
+        .collect::<Vec<_>>()
+        .join(",");
+    csv.push('\n');
+    for report in reports {
+        let row = [report.id.to_string(), report.possible.to_string()].into_iter()
+            .chain(colours.iter().map(|c| report.minimal_bag.count(c).to_string()))
+            .chain([report.power.to_string(), csv_field(report.limiting_colour.as_deref().unwrap_or(""))])
+            .collect::<Vec<_>>();
+        csv += &row.join(",");
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Part 1 with a different bag (`--bag` on the command line).
+pub fn part1_with(input: String, bag: &Cubes) -> SolveResult {
+    Ok(Solution::new(sum_possible(&parse_games(&input)?, bag)))
+}
+
+/// Part 2 with a different bag, whose colours are the ones multiplied together.
+pub fn part2_with(input: String, bag: &Cubes) -> SolveResult {
+    Ok(Solution::new(sum_powers(&parse_games(&input)?, bag)))
+}
+
+/// Sums the ids of the games that are possible with 12 red, 13 green and 14 blue cubes.
+pub fn part1(input: String) -> SolveResult {
+    part1_with(input, &default_bag())
 }
 
-pub(crate) fn part1(input: String) {
-    day2(input, false);
+/// Sums the powers of the smallest set of cubes each game could have been played with.
+pub fn part2(input: String) -> SolveResult {
+    part2_with(input, &default_bag())
 }
 
-pub(crate) fn part2(input: String) {
-    day2(input, true);
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+    use super::*;
+    use crate::testing::{check, property};
+
+    const EXAMPLE: &str = "\
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+";
+
+    #[test]
+    fn part1_example() {
+        check(part1, EXAMPLE, 8);
+    }
+
+    #[test]
+    fn part2_example() {
+        check(part2, EXAMPLE, 2286);
+    }
+
+    #[test]
+    fn games() {
+        let game = parse_games("Game 7: 3 blue, 4 red; 2 teal, 1 red, 1 teal").unwrap().remove(0);
+        assert_eq!(game.id, 7);
+        assert_eq!(game.rounds.iter().map(Cubes::to_string).collect::<Vec<_>>(), ["3 blue, 4 red", "1 red, 3 teal"]);
+        assert_eq!(game.minimal_bag().to_string(), "3 blue, 4 red, 3 teal");
+
+        let error = parse_games("Game 1: 3 blue\nGame 2: 3 blue, red\n").unwrap_err();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 17, "red"));
+    }
+
+    #[test]
+    fn queries() {
+        let games = parse_games(EXAMPLE).unwrap();
+        let bag = default_bag();
+        assert_eq!(possible_games(&games, &bag), [1, 2, 5]);
+
+        let reports = reports(&games, &bag);
+        assert_eq!(reports[2].minimal_bag.to_string(), "6 blue, 13 green, 20 red");
+        assert_eq!((reports[2].possible, reports[2].limiting_colour.as_deref()), (false, Some("red")));
+        assert_eq!((reports[3].power, reports[3].limiting_colour.as_deref()), (630, Some("red")));
+        assert_eq!(reports[0].limiting_colour.as_deref(), Some("blue"));
+        assert_eq!(reports_to_csv(&reports[..2], &bag), "\
+id,possible,blue,green,red,power,limiting_colour
+1,true,6,2,4,48,blue
+2,true,4,3,1,12,blue
+");
+
+        let smallest = |k| smallest_bag_for(&games, k).map(|bag| bag.to_string());
+        assert_eq!(smallest(1).as_deref(), Some("4 blue, 3 green, 1 red"));
+        assert_eq!(smallest(3).as_deref(), Some("6 blue, 3 green, 6 red"));
+        assert_eq!(smallest(5).as_deref(), Some("15 blue, 13 green, 20 red"));
+        assert_eq!(smallest(0).as_deref(), Some(""));
+        assert_eq!(smallest(6), None);
+    }
+
+    // the smallest bag should be as small as the union of the best k games, found by trying every set of them
+    #[test]
+    fn smallest_bag_matches_brute_force() {
+        property("smallest_bag_matches_brute_force", 50, |rng| {
+            let colours = ["red", "green", "blue", "teal"];
+            let games = (1..=rng.gen_range(1..=7)).map(|id| Game {
+                id,
+                rounds: vec![Cubes(colours.iter().take(rng.gen_range(1..=4))
+                    .map(|c| (c.to_string(), rng.gen_range(0..6)))
+                    .collect())],
+            }).collect::<Vec<_>>();
+            let k = rng.gen_range(1..=games.len());
+            let total = |bag: &Cubes| bag.0.values().map(|&n| u64::from(n)).sum::<u64>();
+
+            let expected = (0u32..1 << games.len()).filter(|set| set.count_ones() as usize == k).map(|set| {
+                let chosen = games.iter().enumerate().filter(|(i, _)| set & (1 << i) != 0);
+                total(&chosen.fold(Cubes::default(), |bag, (_, game)| bag.union(&game.minimal_bag())))
+            }).min();
+            let bag = smallest_bag_for(&games, k).unwrap();
+            assert_eq!(Some(total(&bag)), expected);
+            assert!(possible_games(&games, &bag).len() >= k);
+        });
+    }
+
+    #[test]
+    fn other_bags() {
+        let games = parse_games(EXAMPLE).unwrap();
+        assert_eq!(sum_possible(&games, &Cubes::from_str("20 red, 13 green, 15 blue").unwrap()), 1 + 2 + 3 + 4 + 5);
+        assert_eq!(sum_possible(&games, &Cubes::from_str("12 red\n13 green\n").unwrap()), 0);
+
+        // a colour nobody shows makes every power 0, and one only the games show counts too
+        let games = parse_games("Game 1: 2 red, 3 teal; 4 red\n").unwrap();
+        assert_eq!(sum_powers(&games, &Cubes::from_str("1 red, 1 green").unwrap()), 0);
+        assert_eq!(sum_powers(&games, &Cubes::from_str("1 red").unwrap()), 12);
+        assert!(Cubes::from_str("12 red, green").is_err());
+    }
 }
@@ -1,15 +1,31 @@
 use std::collections::{HashMap, VecDeque};
 use std::convert::identity;
 use std::fmt::Debug;
-
-trait Module: Debug {
+use crate::cancel;
+use crate::graph::{self, Graph};
+use crate::number_theory::{self, Cycle};
+use crate::parse::{self, ParseError};
+use crate::solution::{Solution, SolveResult};
+
+crate::registry::puzzle!(2023, 20, "Pulse Propagation", part1, part2, |input| {
+    parse::line_errors(input, |input| parse_input(input.to_string()))
+});
+
+/// A communication module.
+pub trait Module: Debug {
+    /// Handles a pulse from `sender`, returning the pulse to send on to every destination (if any).
     fn receive_pulse(&mut self, is_high: bool, sender: &str) -> Option<bool>;
+    /// The modules this one sends pulses to.
     fn get_destination_ids(&self) -> &Vec<String>;
+    /// Whether the module is back in the state it started in.
     fn is_original_state(&self) -> bool;
+    /// What the module remembers, which along with the pulses it's sent decides what it does next.
+    fn state(&self) -> Vec<bool>;
 }
 
+/// The broadcaster, which passes every pulse on unchanged.
 #[derive(Debug, Eq, PartialEq, Clone)]
-struct BroadcastModule {
+pub struct BroadcastModule {
     destinations: Vec<String>,
 }
 
@@ -25,10 +41,15 @@ impl Module for BroadcastModule {
     fn is_original_state(&self) -> bool {
         true
     }
+
+    fn state(&self) -> Vec<bool> {
+        vec![]
+    }
 }
 
+/// A flip-flop (`%`), which toggles on low pulses and sends its new state.
 #[derive(Debug, Eq, PartialEq, Clone)]
-struct FlipFlopModule {
+pub struct FlipFlopModule {
     destinations: Vec<String>,
     state: bool,
 }
@@ -50,10 +71,15 @@ impl Module for FlipFlopModule {
     fn is_original_state(&self) -> bool {
         !self.state
     }
+
+    fn state(&self) -> Vec<bool> {
+        vec![self.state]
+    }
 }
 
+/// A conjunction (`&`), which sends a low pulse once the last pulse from each of its inputs was high.
 #[derive(Debug, Eq, PartialEq, Clone)]
-struct ConjunctionModule {
+pub struct ConjunctionModule {
     destinations: Vec<String>,
     state: HashMap<String, bool>,
 }
@@ -75,15 +101,22 @@ impl Module for ConjunctionModule {
     fn is_original_state(&self) -> bool {
         self.state.values().all(|&b| !b)
     }
+
+    fn state(&self) -> Vec<bool> {
+        let mut inputs = self.state.iter().collect::<Vec<_>>();
+        inputs.sort();
+        inputs.into_iter().map(|(_, &b)| b).collect()
+    }
 }
 
-fn parse_input(input: String) -> (HashMap<String, Box<dyn Module>>, HashMap<String, Vec<String>>) {
+/// Parses the module configuration into the modules by name, and the graph of which modules send pulses to which.
+pub fn parse_input(input: String) -> Result<(HashMap<String, Box<dyn Module>>, Graph<String, ()>), ParseError> {
     let mut v: HashMap<String, Box<dyn Module>> = HashMap::new();
     let mut conjunctions: HashMap<String, ConjunctionModule> = HashMap::new();
-    let mut inputs: HashMap<String, Vec<String>> = HashMap::new();
+    let mut wiring = Graph::new();
 
-    for line in input.lines() {
-        let (name, targets) = line.split_once(" -> ").unwrap();
+    for line in parse::lines(DAY, &input) {
+        let (name, targets) = line.split_once(line.text, " -> ")?;
         let targets = targets.split(", ").collect::<Vec<&str>>();
         let (typeid, name) = if name.starts_with("%") {
             ("%", &name[1..])
@@ -92,13 +125,14 @@ fn parse_input(input: String) -> (HashMap<String, Box<dyn Module>>, HashMap<Stri
         } else {
             ("", name)
         };
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(line.error(name, "invalid module name"));
+        }
 
+        let source = wiring.add_node(name.to_string());
         for target in &targets {
-            if !inputs.contains_key(&target.to_string()) {
-                inputs.insert(target.to_string(), vec![name.to_string()]);
-            } else {
-                inputs.get_mut(&target.to_string()).unwrap().push(name.to_string());
-            }
+            let target = wiring.add_node(target.to_string());
+            wiring.add_edge(source, target, ());
         }
 
         let destinations = targets.into_iter().map(ToOwned::to_owned).collect::<Vec<_>>();
@@ -129,23 +163,33 @@ fn parse_input(input: String) -> (HashMap<String, Box<dyn Module>>, HashMap<Stri
     }
 
     for (name, mut conjunction) in conjunctions.into_iter() {
-        let in_ids = inputs.get(&name).unwrap();
-        for id in in_ids {
-            conjunction.state.insert(id.to_string(), false);
+        let id = wiring.id(&name).unwrap();
+        for input in wiring.predecessors(id) {
+            conjunction.state.insert(wiring.node(input).to_string(), false);
         }
         v.insert(name, Box::new(conjunction));
     }
 
-    (v, inputs)
+    if !v.contains_key("broadcaster") {
+        return Err(ParseError::whole_input(DAY, "there is no broadcaster module"));
+    }
+
+    Ok((v, wiring))
 }
 
-struct Pulse {
-    is_high: bool,
-    source: String,
-    destination: String,
+/// A pulse on its way between two modules.
+pub struct Pulse {
+    /// High or low.
+    pub is_high: bool,
+    /// The module that sent it.
+    pub source: String,
+    /// The module it's going to.
+    pub destination: String,
 }
 
-fn handle_pulses(modules: &mut HashMap<String, Box<dyn Module>>, pulses: &mut VecDeque<Pulse>, looking_for: &[String]) -> (usize, usize, Vec<bool>) {
+/// Pushes the button once and handles pulses until there are none left. Returns the number of low and high
+/// pulses sent, and for each module in `looking_for` whether it sent a high pulse.
+pub fn handle_pulses(modules: &mut HashMap<String, Box<dyn Module>>, pulses: &mut VecDeque<Pulse>, looking_for: &[String]) -> (usize, usize, Vec<bool>) {
     pulses.push_back(Pulse {
         is_high: false,
         source: "button".to_string(),
@@ -189,8 +233,9 @@ fn handle_pulses(modules: &mut HashMap<String, Box<dyn Module>>, pulses: &mut Ve
     (low_sent, high_sent, v)
 }
 
-pub(crate) fn part1(input: String) {
-    let (mut modules, _) = parse_input(input);
+/// The number of low pulses times the number of high pulses sent after pushing the button 1000 times.
+pub fn part1(input: String) -> SolveResult {
+    let (mut modules, _) = parse_input(input)?;
     let mut pulses = VecDeque::new();
     let mut values = vec![];
 
@@ -214,39 +259,155 @@ pub(crate) fn part1(input: String) {
         total_high_sent += additional_high_sent;
     }
 
-    println!("{}", total_high_sent * total_low_sent);
-}
-
-fn gcd(a: u64, b: u64) -> u64 {
-    if b == 0 {
-        return a;
-    }
-
-    gcd(b, a.rem_euclid(b))
+    Ok(Solution::new(total_high_sent * total_low_sent))
 }
 
-fn lcm(a: u64, b: u64) -> u64 {
-    a * (b / gcd(a, b))
-}
+// how long to wait for the inputs to rx's module to repeat; in the real inputs they're 12-bit counters
+const MAX_PRESSES: u64 = 1 << 16;
 
-pub(crate) fn part2(input: String) {
-    let (mut modules, inputs) = parse_input(input);
+/// The fewest button presses before a single low pulse is sent to `rx`.
+pub fn part2(input: String) -> SolveResult {
+    let (mut modules, wiring) = parse_input(input)?;
     let mut pulses = VecDeque::new();
-    let looking_for = inputs.get(&inputs.get("rx").unwrap()[0]).unwrap();
-    let mut periods: Vec<u64> = vec![0; looking_for.len()];
+    let looking_for = wiring
+        .id("rx")
+        .and_then(|rx| wiring.predecessors(rx).first().copied())
+        .map(|feeder| wiring.predecessors(feeder).into_iter().map(|id| wiring.node(id).clone()).collect::<Vec<_>>())
+        .filter(|inputs| !inputs.is_empty())
+        .ok_or_else(|| ParseError::whole_input(DAY, "rx is not fed by a module with its own inputs"))?;
+    // each input only depends on the modules upstream of it, so once they're all in a state they've been in before,
+    // the presses it sent a high pulse on since then repeat forever
+    let upstream = looking_for
+        .iter()
+        .map(|input| {
+            let id = wiring.id(input).unwrap();
+            graph::dfs(id, |&id| wiring.predecessors(id)).into_iter().map(|id| wiring.node(id).clone()).collect()
+        })
+        .collect::<Vec<Vec<String>>>();
+    let state = |modules: &HashMap<String, Box<dyn Module>>, names: &[String]| {
+        names.iter().flat_map(|name| modules.get(name).map_or(vec![], |m| m.state())).collect::<Vec<_>>()
+    };
+    let mut seen = upstream.iter().map(|names| HashMap::from([(state(&modules, names), 0)])).collect::<Vec<_>>();
+    let mut high_presses: Vec<Vec<u64>> = vec![vec![]; looking_for.len()];
+    // for each input, the press its cycle starts after and the cycle's length
+    let mut repeats: Vec<Option<(u64, u64)>> = vec![None; looking_for.len()];
     let mut presses = 0;
-    loop {
+    while repeats.iter().any(Option::is_none) {
+        if presses == MAX_PRESSES {
+            return Err(ParseError::whole_input(DAY, format!("the inputs to rx's module don't all repeat within {MAX_PRESSES} presses")));
+        }
         presses += 1;
-        let (_, _, v) = handle_pulses(&mut modules, &mut pulses, looking_for);
+        cancel::checkpoint_with(|| format!("{presses} presses without every input to rx's module repeating"));
+        let (_, _, v) = handle_pulses(&mut modules, &mut pulses, &looking_for);
         for (idx, b) in v.into_iter().enumerate() {
-            if b && periods[idx] == 0 {
-                periods[idx] = presses;
+            if repeats[idx].is_some() {
+                continue;
+            }
+            if b {
+                high_presses[idx].push(presses);
+            }
+            if let Some(start) = seen[idx].insert(state(&modules, &upstream[idx]), presses) {
+                repeats[idx] = Some((start, presses - start));
             }
         }
-        if periods.iter().all(|&p| p != 0) {
-            break;
-        }
     }
 
-    println!("{}", periods.into_iter().reduce(lcm).unwrap());
+    // either they line up before one of them starts repeating, or it's when one press from each of their cycles
+    // lines up
+    let cycles = high_presses.iter().zip(&repeats).map(|(presses, repeat)| {
+        let (start, period) = repeat.unwrap();
+        let (before, during): (Vec<u64>, Vec<u64>) = presses.iter().partition(|&&p| p <= start);
+        (before, during.into_iter().map(|offset| Cycle { offset, period }).collect::<Vec<_>>())
+    }).collect::<Vec<_>>();
+    for (input, (before, during)) in looking_for.iter().zip(&cycles) {
+        log::debug!("{input} sends a high pulse on presses {before:?} before repeating, then at {during:?}");
+    }
+    let on_high = |(before, during): &(Vec<u64>, Vec<Cycle>), press| {
+        before.contains(&press) || during.iter().any(|c: &Cycle| c.contains(press))
+    };
+    let early = cycles
+        .iter()
+        .flat_map(|(before, _)| before.iter().copied())
+        .filter(|&press| cycles.iter().all(|c| on_high(c, press)))
+        .min();
+    let mut combinations = vec![vec![]];
+    for (_, during) in &cycles {
+        combinations = combinations.into_iter().flat_map(|combination: Vec<Cycle>| {
+            during.iter().map(move |&cycle| [combination.clone(), vec![cycle]].concat())
+        }).collect();
+    }
+    let looping = combinations.iter().filter_map(|cycles| number_theory::first_common_time(cycles)).min();
+    let presses = early.or(looping).ok_or_else(|| ParseError::whole_input(DAY, "the inputs to rx's module never all send a high pulse on the same press"))?;
+    Ok(Solution::new(presses))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::check;
+
+    const EXAMPLE_1: &str = "\
+broadcaster -> a, b, c
+%a -> b
+%b -> c
+%c -> inv
+&inv -> a
+";
+
+    const EXAMPLE_2: &str = "\
+broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output
+";
+
+    const RX_EXAMPLE: &str = "\
+broadcaster -> a1, b1
+%a1 -> a2, c1
+%a2 -> c1
+&c1 -> a1, i1
+&i1 -> f
+%b1 -> b2, c2
+%b2 -> b3
+%b3 -> c2
+&c2 -> b1, b2, i2
+&i2 -> f
+&f -> rx
+";
+
+    #[test]
+    fn part1_example_1() {
+        check(part1, EXAMPLE_1, 32000000);
+    }
+
+    #[test]
+    fn part1_example_2() {
+        check(part1, EXAMPLE_2, 11687500);
+    }
+
+    // the puzzle has no example for part 2, so this is a small network built like the real ones: `rx` is fed by a
+    // conjunction of two inverters, each of which sends a high pulse (and then a low one) when its counter wraps,
+    // every 3 and every 5 presses
+    #[test]
+    fn part2_example() {
+        check(part2, RX_EXAMPLE, 15);
+    }
+
+    // `a` sends a high pulse on every odd press and `c` on presses 2, 6, 10, ..., so they never line up, though the
+    // first of each (1 and 2) do have a common multiple
+    #[test]
+    fn part2_inputs_that_never_line_up() {
+        let input = "\
+broadcaster -> a, b
+%a -> con
+%b -> c
+%c -> con
+&con -> rx
+";
+        assert_eq!(
+            part2(input.to_string()).unwrap_err().message,
+            "the inputs to rx's module never all send a high pulse on the same press",
+        );
+    }
 }
@@ -1,22 +1,26 @@
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::ops::{Index, IndexMut};
-
-fn parse_input(input: String) -> (Vec<Vec<bool>>, (isize, isize)) {
-    let mut start = (0, 0);
-    let v = input.lines().enumerate().map(|(y, line)| {
-        line.chars().enumerate().map(|(x, c)| {
-            if c == 'S' {
-                start = (x as isize, y as isize);
-            }
-
-            match c {
-                '.' | 'S' => true,
-                '#' => false,
-                _ => panic!("Invalid character in input!"),
-            }
-        }).collect::<Vec<_>>()
-    }).collect::<Vec<_>>();
-    (v, start)
+use crate::cancel;
+use crate::grid::Grid;
+use crate::parse::{self, ParseError};
+use crate::solution::{Solution, SolveResult};
+
+crate::registry::puzzle!(2023, 21, "Step Counter", part1, part2, |input| {
+    parse::line_errors(input, |input| parse_input(input.to_string()))
+});
+
+/// Parses the map into garden plots (`true`) and rocks, and finds the starting position.
+pub fn parse_input(input: String) -> Result<(Grid<bool>, (isize, isize)), ParseError> {
+    let start = input
+        .lines()
+        .enumerate()
+        .find_map(|(y, line)| line.chars().position(|c| c == 'S').map(|x| (x as isize, y as isize)))
+        .ok_or_else(|| ParseError::whole_input(DAY, "grid does not contain a starting position (S)"))?;
+    let v = Grid::parse(DAY, &input, |c| match c {
+        '.' | 'S' => Some(true),
+        '#' => Some(false),
+        _ => None,
+    })?;
+    Ok((v, start))
 }
 
 struct State {
@@ -24,36 +28,15 @@ struct State {
     steps: usize,
 }
 
-struct WrappingGrid(Vec<Vec<bool>>);
-
-impl Index<(isize, isize)> for WrappingGrid {
-    type Output = bool;
-
-    fn index(&self, index: (isize, isize)) -> &Self::Output {
-        let (x, y) = index;
-        let w = self.0[0].len() as isize;
-        let h = self.0.len() as isize;
-
-        &self.0[y.rem_euclid(h) as usize][x.rem_euclid(w) as usize]
-    }
-}
-
-impl IndexMut<(isize, isize)> for WrappingGrid {
-    fn index_mut(&mut self, index: (isize, isize)) -> &mut Self::Output {
-        let (x, y) = index;
-        let w = self.0[0].len() as isize;
-        let h = self.0.len() as isize;
-
-        &mut self.0[y.rem_euclid(h) as usize][x.rem_euclid(w) as usize]
-    }
-}
-
-fn search(grid: &WrappingGrid, start: (isize, isize), max_steps: usize) -> HashSet<(isize, isize)> {
+/// The garden plots that can be reached in exactly `max_steps` steps on the map repeated infinitely in every
+/// direction.
+pub fn search(grid: &Grid<bool>, start: (isize, isize), max_steps: usize) -> HashSet<(isize, isize)> {
     let mut queue = VecDeque::new();
     let mut output = HashSet::new();
     let mut visited = HashMap::new();
     queue.push_back(State { pos: start, steps: 0 });
     while let Some(state) = queue.pop_front() {
+        cancel::checkpoint();
         if state.steps == max_steps {
             output.insert(state.pos);
             continue;
@@ -67,16 +50,16 @@ fn search(grid: &WrappingGrid, start: (isize, isize), max_steps: usize) -> HashS
         }
         visited.insert((x, y), state.steps);
 
-        if grid[(x, y - 1)] {
+        if *grid.get_wrapping(x, y - 1) {
             queue.push_back(State { pos: (x, y - 1), steps: state.steps + 1 });
         }
-        if grid[(x, y + 1)] {
+        if *grid.get_wrapping(x, y + 1) {
             queue.push_back(State { pos: (x, y + 1), steps: state.steps + 1 });
         }
-        if grid[(x - 1, y)] {
+        if *grid.get_wrapping(x - 1, y) {
             queue.push_back(State { pos: (x - 1, y), steps: state.steps + 1 });
         }
-        if grid[(x + 1, y)] {
+        if *grid.get_wrapping(x + 1, y) {
             queue.push_back(State { pos: (x + 1, y), steps: state.steps + 1 });
         }
     }
@@ -84,24 +67,100 @@ fn search(grid: &WrappingGrid, start: (isize, isize), max_steps: usize) -> HashS
     output
 }
 
-pub(crate) fn part1(input: String) {
-    let (grid, start) = parse_input(input);
-    let grid = WrappingGrid(grid);
-    let points = search(&grid, start, 64);
-    println!("{}", points.len());
+/// The number of garden plots that can be reached in exactly `steps` steps.
+pub fn reachable_plots(input: String, steps: usize) -> SolveResult {
+    let (grid, start) = parse_input(input)?;
+    let points = search(&grid, start, steps);
+    Ok(Solution::new(points.len()))
 }
 
-pub(crate) fn part2(input: String) {
-    let (grid, start) = parse_input(input);
-    let grid = WrappingGrid(grid);
+/// The number of garden plots that can be reached in exactly 64 steps.
+pub fn part1(input: String) -> SolveResult {
+    reachable_plots(input, 64)
+}
 
-    // hardcoded values for all official inputs
-    let a = search(&grid, start, 65 + 0 * 131).len() as isize;
-    let b = search(&grid, start, 65 + 1 * 131).len() as isize;
-    let c = search(&grid, start, 65 + 2 * 131).len() as isize;
+/// Extrapolates the number of garden plots reachable in exactly `steps` steps on a square map of odd size `n` with the
+/// start in the middle, by fitting a quadratic to the exact counts after `n / 2`, `n / 2 + n` and `n / 2 + 2n` steps.
+/// This only holds when the start's row and column and the map's edges are clear, and `steps` is `n / 2` more than a
+/// multiple of `n`.
+pub fn extrapolate_plots(grid: &Grid<bool>, start: (isize, isize), steps: usize) -> isize {
+    let n = grid.height();
+    let a = search(grid, start, n / 2).len() as isize;
+    let b = search(grid, start, n / 2 + n).len() as isize;
+    let c = search(grid, start, n / 2 + 2 * n).len() as isize;
     let ca = ((c - b) - (b - a)) / 2;
     let cb = (b - 4 * ca) - (a - ca);
     let cc = (a - ca) - cb;
-    let x = 202301isize;
-    println!("{}", ca * x * x + cb * x + cc);
-}
\ No newline at end of file
+    log::debug!("{a}, {b} and {c} plots after {} steps (and n, 2n more) fit {ca}x² + {cb}x + {cc}", n / 2);
+    let x = (steps / n) as isize + 1;
+    ca * x * x + cb * x + cc
+}
+
+/// The number of garden plots that can be reached in exactly 26501365 steps on the infinitely repeating map.
+pub fn part2(input: String) -> SolveResult {
+    let (grid, start) = parse_input(input)?;
+    Ok(Solution::new(extrapolate_plots(&grid, start, 26501365)))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+    use super::*;
+    use crate::testing::{check, property};
+
+    const EXAMPLE: &str = "\
+...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........
+";
+
+    #[test]
+    fn part1_example() {
+        check(|input| reachable_plots(input, 6), EXAMPLE, 16);
+    }
+
+    // the example doesn't have the clear middle row and column the part 2 extrapolation relies on, so check the exact
+    // counts on the infinite map that the puzzle gives instead
+    #[test]
+    fn infinite_map_10_steps() {
+        check(|input| reachable_plots(input, 10), EXAMPLE, 50);
+    }
+
+    #[test]
+    fn infinite_map_50_steps() {
+        check(|input| reachable_plots(input, 50), EXAMPLE, 1594);
+    }
+
+    #[test]
+    fn infinite_map_100_steps() {
+        check(|input| reachable_plots(input, 100), EXAMPLE, 6536);
+    }
+
+    // part 2 extrapolates from a few exact searches, which should agree with searching all the way on maps shaped like
+    // the real input (an odd-sized square with the start in the middle, and a clear middle row, column and border)
+    #[test]
+    fn extrapolation_matches_search() {
+        property("extrapolation_matches_search", 20, |rng| {
+            let n = rng.gen_range(2..=5) * 2 + 1;
+            let density = rng.gen_range(0.0..0.2);
+            let grid = Grid::from_rows((0..n).map(|y| (0..n).map(|x| {
+                let clear = x == 0 || y == 0 || x == n - 1 || y == n - 1 || x == n / 2 || y == n / 2;
+                clear || !rng.gen_bool(density)
+            }).collect()).collect());
+            let start = ((n / 2) as isize, (n / 2) as isize);
+
+            for k in 3..=4 {
+                let steps = n / 2 + k * n;
+                assert_eq!(extrapolate_plots(&grid, start, steps), search(&grid, start, steps).len() as isize);
+            }
+        });
+    }
+}
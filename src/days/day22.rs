@@ -1,21 +1,36 @@
-use std::str::FromStr;
+use crate::cancel;
+use crate::parse::{self, Line, ParseError};
+use crate::solution::{Solution, SolveResult};
 
+crate::registry::puzzle!(2023, 22, "Sand Slabs", part1, part2, |input| {
+    parse::line_errors(input, |input| parse_input(input.to_string()))
+});
+
+/// The axis a brick lies along.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-enum Axis {
+pub enum Axis {
+    /// x
     X,
+    /// y
     Y,
+    /// z (up)
     Z,
 }
 
+/// A position in the snapshot.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-struct Point3D {
-    x: i32,
-    y: i32,
-    z: i32,
+pub struct Point3D {
+    /// x
+    pub x: i32,
+    /// y
+    pub y: i32,
+    /// z (height)
+    pub z: i32,
 }
 
 impl Point3D {
-    fn add_axis(&self, axis: Axis, amount: i32) -> Self {
+    /// Moves the point `amount` along `axis`.
+    pub fn add_axis(&self, axis: Axis, amount: i32) -> Self {
         match axis {
             Axis::X => Point3D { x: self.x + amount, y: self.y, z: self.z },
             Axis::Y => Point3D { x: self.x, y: self.y + amount, z: self.z },
@@ -24,19 +39,25 @@ impl Point3D {
     }
 }
 
+/// A straight brick of `length` cubes along `axis` from `start`.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-struct Brick {
-    axis: Axis,
-    start: Point3D,
-    length: i32,
+pub struct Brick {
+    /// The axis the brick lies along.
+    pub axis: Axis,
+    /// The cube with the lowest coordinate.
+    pub start: Point3D,
+    /// The number of cubes.
+    pub length: i32,
 }
 
 impl Brick {
-    fn contains_any_point(&self, points: &[Point3D]) -> bool {
+    /// Whether any cube of the brick is at one of `points`.
+    pub fn contains_any_point(&self, points: &[Point3D]) -> bool {
         (0..self.length).any(|p| points.contains(&self.start.add_axis(self.axis, p)))
     }
 
-    fn get_bottom_points(&self) -> Vec<Point3D> {
+    /// The cubes on the bottom face of the brick.
+    pub fn get_bottom_points(&self) -> Vec<Point3D> {
         if self.axis == Axis::Z {
             vec![self.start]
         } else {
@@ -44,7 +65,8 @@ impl Brick {
         }
     }
 
-    fn get_supporting_bricks(&self, others: &[Brick]) -> usize {
+    /// The number of `others` directly underneath the brick.
+    pub fn get_supporting_bricks(&self, others: &[Brick]) -> usize {
         let bottom_points = Brick {
             axis: self.axis,
             start: self.start.add_axis(Axis::Z, -1),
@@ -53,7 +75,8 @@ impl Brick {
         others.iter().filter(|&b| b != self && b.contains_any_point(&bottom_points)).count()
     }
 
-    fn move_down_if_possible(&self, others: &[Brick]) -> (Brick, bool) {
+    /// The brick moved down one step, if nothing is holding it up, and whether it moved.
+    pub fn move_down_if_possible(&self, others: &[Brick]) -> (Brick, bool) {
         if self.get_supporting_bricks(others) == 0 && self.start.z > 1 {
             (Brick {
                 axis: self.axis,
@@ -65,11 +88,13 @@ impl Brick {
         }
     }
 
-    fn is_supported_by(&self, other: Brick) -> bool {
+    /// Whether `other` is directly underneath the brick.
+    pub fn is_supported_by(&self, other: Brick) -> bool {
         self.get_supporting_bricks(&[other]) == 1
     }
 
-    fn can_disintegrate(&self, others: &[Brick]) -> bool {
+    /// Whether the brick can be removed without any of `others` falling.
+    pub fn can_disintegrate(&self, others: &[Brick]) -> bool {
         for brick in others {
             if brick.is_supported_by(*self) && brick.get_supporting_bricks(others) == 1 {
                 return false;
@@ -79,13 +104,23 @@ impl Brick {
     }
 }
 
-fn parse_input(input: String) -> Vec<Brick> {
-    input.lines().map(|line| {
-        let (c1, c2) = line.split_once('~').unwrap();
-        let coords1 = c1.split(',').map(|c| i32::from_str(c).unwrap()).collect::<Vec<_>>();
-        let coords2 = c2.split(',').map(|c| i32::from_str(c).unwrap()).collect::<Vec<_>>();
-        let (x1, y1, z1) = (coords1[0], coords1[1], coords1[2]);
-        let (x2, y2, z2) = (coords2[0], coords2[1], coords2[2]);
+fn parse_coords(line: Line, s: &str) -> Result<(i32, i32, i32), ParseError> {
+    let coords = s.split(',').map(|c| line.parse(c, "coordinate")).collect::<Result<Vec<_>, _>>()?;
+    match coords[..] {
+        [x, y, z] => Ok((x, y, z)),
+        _ => Err(line.error(s, "expected 3 coordinates")),
+    }
+}
+
+/// Parses the snapshot of bricks.
+pub fn parse_input(input: String) -> Result<Vec<Brick>, ParseError> {
+    parse::lines(DAY, &input).map(|line| {
+        let (c1, c2) = line.split_once(line.text, "~")?;
+        let (x1, y1, z1) = parse_coords(line, c1)?;
+        let (x2, y2, z2) = parse_coords(line, c2)?;
+        if [x1 != x2, y1 != y2, z1 != z2].into_iter().filter(|&b| b).count() > 1 {
+            return Err(line.error(line.text, "bricks must be straight lines"));
+        }
 
         let (axis, length) = if x1 == x2 && y1 == y2 {
             (Axis::Z, z2.abs_diff(z1) as i32 + 1)
@@ -95,15 +130,16 @@ fn parse_input(input: String) -> Vec<Brick> {
             (Axis::X, x2.abs_diff(x1) as i32 + 1)
         };
 
-        Brick {
+        Ok(Brick {
             axis,
             start: Point3D { x: x1, y: y1, z: z1 },
             length,
-        }
-    }).collect::<Vec<Brick>>()
+        })
+    }).collect()
 }
 
-fn fall_bricks(bricks: &mut [Brick]) -> usize {
+/// Lets the bricks fall until they settle, returning how many of them moved.
+pub fn fall_bricks(bricks: &mut [Brick]) -> usize {
     let mut fell = vec![false; bricks.len()];
     let mut moved_any = true;
     while moved_any {
@@ -120,23 +156,52 @@ fn fall_bricks(bricks: &mut [Brick]) -> usize {
     fell.into_iter().filter(|&b| b).count()
 }
 
-pub(crate) fn part1(input: String) {
-    let mut bricks = parse_input(input);
+/// The number of bricks that could safely be disintegrated once the bricks have settled.
+pub fn part1(input: String) -> SolveResult {
+    let mut bricks = parse_input(input)?;
     fall_bricks(&mut bricks);
-    println!("{}", bricks.iter().filter(|&b| b.can_disintegrate(&bricks)).count());
+    Ok(Solution::new(bricks.iter().filter(|&b| b.can_disintegrate(&bricks)).count()))
 }
 
-pub(crate) fn part2(input: String) {
-    let mut bricks = parse_input(input);
+/// Sums, for each brick, the number of other bricks that would fall if it were disintegrated.
+pub fn part2(input: String) -> SolveResult {
+    let mut bricks = parse_input(input)?;
     fall_bricks(&mut bricks);
 
     let mut total_fell = 0;
     for i in 0..bricks.len() {
-        println!("processing brick {}/{}", i + 1, bricks.len());
+        log::debug!("processing brick {}/{}", i + 1, bricks.len());
+        cancel::checkpoint_with(|| format!("{total_fell} bricks fell for the first {i} of {}", bricks.len()));
         let mut b = bricks.clone();
         b.remove(i);
         total_fell += fall_bricks(&mut b);
     }
 
-    println!("{total_fell}");
+    Ok(Solution::new(total_fell))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::check;
+
+    const EXAMPLE: &str = "\
+1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9
+";
+
+    #[test]
+    fn part1_example() {
+        check(part1, EXAMPLE, 5);
+    }
+
+    #[test]
+    fn part2_example() {
+        check(part2, EXAMPLE, 7);
+    }
 }
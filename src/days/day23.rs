@@ -1,232 +1,184 @@
-use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
-use std::rc::Rc;
-use derivative::Derivative;
-
-fn parse_input(input: String) -> Vec<Vec<char>> {
-    input.lines().map(|l| l.chars().collect()).collect()
-}
-
-#[derive(Eq, PartialEq, Clone, Ord, PartialOrd, Derivative)]
-#[derivative(Debug)]
-struct State {
-    length: isize,
-    pos: (usize, usize),
-    #[derivative(Debug="ignore")]
-    prev: Option<Rc<State>>,
-}
-
-fn clone_option_rc<T>(option: &Option<Rc<T>>) -> Option<Rc<T>> {
-    option.as_ref().map(|rc| Rc::clone(rc))
-}
-
-fn longest_path_dijkstra(grid: &[Vec<char>], start_pos: (usize, usize), goal_pos: (usize, usize)) -> Option<State> {
-    let (sx, sy) = start_pos;
-    let mut dist = vec![vec![-1; grid[0].len()]; grid.len()];
-    let mut queue = VecDeque::new();
-    let mut paths = BinaryHeap::new();
-    queue.push_back(State { length: 0, pos: start_pos, prev: None });
-    let mut sv = 0;
-    while let Some(state) = queue.pop_front() {
-        sv += 1;
-        let State { length, pos: (x, y), prev } = &state;
-        let length = *length;
-        let (x, y) = (*x, *y);
-
-        if (x, y) == goal_pos {
-            paths.push(state);
-            continue;
-        }
-
-        if length < dist[y][x] {
-            continue;
-        }
-
-        let mut visited = HashSet::new();
-        let mut p = clone_option_rc(prev);
-        while let Some(s) = p {
-            visited.insert(s.pos);
-            p = clone_option_rc(&s.prev);
-        }
-
-        let mut adj = Vec::with_capacity(3);
-
-        match grid[y][x] {
-            '.' => {
-                if x > 0 && !visited.contains(&(x - 1, y)) && grid[y][x - 1] != '#' {
-                    adj.push((x - 1, y));
-                }
-                if x < grid[0].len() - 1 && !visited.contains(&(x + 1, y)) && grid[y][x + 1] != '#' {
-                    adj.push((x + 1, y));
-                }
-                if y > 0 && !visited.contains(&(x, y - 1)) && grid[y - 1][x] != '#' {
-                    adj.push((x, y - 1));
-                }
-                if y < grid.len() - 1 && !visited.contains(&(x, y + 1)) && grid[y + 1][x] != '#' {
-                    adj.push((x, y + 1));
-                }
-            },
-            'v' => if y < grid.len() - 1 && !visited.contains(&(x, y + 1)) && grid[y + 1][x] != '#' {
-                adj.push((x, y + 1));
-            },
-            '>' => if x < grid[0].len() - 1 && !visited.contains(&(x + 1, y)) && grid[y][x + 1] != '#' {
-                adj.push((x + 1, y));
-            },
-            '^' => if y > 0 && !visited.contains(&(x, y - 1)) && grid[y - 1][x] != '#' {
-                adj.push((x, y - 1));
-            },
-            '<' => if x > 0 && !visited.contains(&(x - 1, y)) && grid[y][x - 1] != '#' {
-                adj.push((x - 1, y));
-            },
-            _ => panic!("invalid input!"),
-        }
-
-
-        for (nx, ny) in adj {
-            let next = State { length: length + 1, pos: (nx, ny), prev: Some(Rc::new(State { length, pos: (x, y), prev: clone_option_rc(prev) }))};
-            if length + 1 > dist[ny][nx] {
-                queue.push_back(next);
-                dist[ny][nx] = length + 1;
-            }
-        }
+use std::collections::HashSet;
+use crate::graph::Graph;
+use crate::grid::Grid;
+use crate::parse::{self, ParseError};
+use crate::render::{Frame, Rgb};
+use crate::solution::{Solution, SolveResult};
+
+crate::registry::puzzle!(2023, 23, "A Long Walk", part1, part2, |input| {
+    parse::line_errors(input, |input| parse_input(input.to_string()))
+});
+
+/// Parses the trail map, checking the start and end are open.
+pub fn parse_input(input: String) -> Result<Grid<char>, ParseError> {
+    let grid = Grid::parse(DAY, &input, |c| "#.<>^v".contains(c).then_some(c))?;
+    if grid.height() < 2 || grid.width() < 3 || grid[(1, 0)] != '.' || grid[(grid.width() - 2, grid.height() - 1)] != '.' {
+        return Err(ParseError::whole_input(DAY, "the start and end must be the second and second-to-last tiles of the first and last rows"));
     }
-
-    paths.pop()
-}
-
-pub(crate) fn part1(input: String) {
-    let grid = parse_input(input);
-    let solution = longest_path_dijkstra(&grid, (1, 0), (grid[0].len() - 2, grid.len() - 1)).unwrap();
-    let mut visited = HashSet::new();
-    let mut ll = Some(Rc::new(solution.clone()));
-    while let Some(state) = ll {
-        visited.insert(state.pos);
-        ll = clone_option_rc(&state.prev);
-    }
-
-    for y in 0..grid.len() {
-        for x in 0..grid[0].len() {
-            if visited.contains(&(x, y)) {
-                print!("O");
-            } else {
-                print!("{}", grid[y][x]);
-            }
-        }
-        println!();
-    }
-    println!();
-
-    println!("{}", solution.length);
+    Ok(grid)
 }
 
-
-fn parse_input_part2(input: String) -> Vec<Vec<char>> {
-    input.lines().map(|l| l.chars().map(|c| match c {
+/// Parses the trail map, treating slopes as normal paths.
+pub fn parse_input_part2(input: String) -> Result<Grid<char>, ParseError> {
+    Ok(parse_input(input)?.map(|&c| match c {
         '#' => '#',
         _ => '.',
-    }).collect()).collect()
+    }))
 }
 
-fn is_intersection(grid: &[Vec<char>], point: (usize, usize)) -> bool {
-    let mut v = Vec::with_capacity(4);
-    let (x, y) = point;
-    if x > 0 {
-        v.push(grid[y][x - 1]);
-    }
-    if x < grid[0].len() - 1 {
-        v.push(grid[y][x + 1]);
-    }
-    if y > 0 {
-        v.push(grid[y - 1][x]);
-    }
-    if y < grid.len() - 1 {
-        v.push(grid[y + 1][x]);
-    }
+// a tile on the map, as (x, y)
+type Pos = (usize, usize);
 
-    v.into_iter().filter(|&c| c != '#').count() > 2
+fn is_intersection(grid: &Grid<char>, point: Pos) -> bool {
+    grid.neighbours4(point).filter(|&p| grid[p] != '#').count() > 2
 }
 
-fn adjacent(grid: &[Vec<char>], pos: (usize, usize)) -> Vec<(usize, usize)> {
-    let (x, y) = pos;
-    let mut adj = Vec::with_capacity(4);
-    if x > 0 && grid[y][x - 1] == '.' {
-        adj.push((x - 1, y));
-    }
-    if x < grid[0].len() - 1 && grid[y][x + 1] == '.' {
-        adj.push((x + 1, y));
-    }
-    if y > 0 && grid[y - 1][x] == '.' {
-        adj.push((x, y - 1));
-    }
-    if y < grid.len() - 1 && grid[y + 1][x] == '.' {
-        adj.push((x, y + 1));
-    }
-
-    adj
+// the tiles that can be stepped to from `pos`: any open neighbour, but only downhill off a slope
+fn steps(grid: &Grid<char>, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+    let moves: &[(isize, isize)] = match grid[pos] {
+        'v' => &[(0, 1)],
+        '>' => &[(1, 0)],
+        '^' => &[(0, -1)],
+        '<' => &[(-1, 0)],
+        _ => &[(-1, 0), (1, 0), (0, -1), (0, 1)],
+    };
+    moves.iter().filter_map(move |&offset| grid.offset(pos, offset)).filter(|&p| grid[p] != '#')
 }
 
-fn next_pos(grid: &[Vec<char>], pos: (usize, usize), prev: (usize, usize)) -> (usize, usize) {
-    adjacent(grid, pos).into_iter().filter(|&p| p != prev).next().unwrap()
+// follows the trail from `from` through `first` to the next of `poi`, returning it and the tiles on the way (not
+// counting `from`); `None` if the trail is a dead end or a slope points the wrong way
+fn follow(
+    grid: &Grid<char>,
+    poi: &HashSet<Pos>,
+    from: Pos,
+    first: Pos,
+) -> Option<(Pos, Vec<Pos>)> {
+    let (mut prev, mut pos) = (from, first);
+    let mut tiles = vec![first];
+    while !poi.contains(&pos) {
+        let next = steps(grid, pos).find(|&p| p != prev)?;
+        (prev, pos) = (pos, next);
+        tiles.push(pos);
+    }
+    Some((pos, tiles))
 }
 
-fn generate_graph(grid: Vec<Vec<char>>, start: (usize, usize), end: (usize, usize)) -> HashMap<(usize, usize), HashMap<(usize, usize), usize>> {
+fn points_of_interest(grid: &Grid<char>, start: Pos, end: Pos) -> HashSet<Pos> {
     let mut poi = HashSet::from([start, end]);
-    for (y, l) in grid.iter().enumerate() {
-        for (x, c) in l.iter().enumerate() {
-            if *c == '.' && is_intersection(&grid, (x, y)) {
-                poi.insert((x, y));
-            }
+    for ((x, y), &c) in grid.iter() {
+        if c != '#' && is_intersection(grid, (x, y)) {
+            poi.insert((x, y));
         }
     }
+    poi
+}
 
-    let mut graph: HashMap<(usize, usize), HashMap<(usize, usize), usize>> = HashMap::new();
-
-    for &(x, y) in &poi {
-        for mut pos in adjacent(&grid, (x, y)) {
-            let mut prev = (x, y);
-            let mut weight = 0;
-            while !poi.contains(&pos) {
-                let tmp = pos;
-                pos = next_pos(&grid, pos, prev);
-                prev = tmp;
-                weight += 1;
-            }
-
-            if let Some(m) = graph.get_mut(&(x, y)) {
-                m.insert(pos, weight + 1);
-            } else {
-                let mut m = HashMap::new();
-                m.insert(pos, weight + 1);
-                graph.insert((x, y), m);
+/// Compresses the trails into a directed graph between the junctions (and `start` and `end`), weighted by the
+/// length of the trail between them. A trail is only an edge in the directions its slopes allow, so a grid without
+/// slopes gives each edge both ways.
+pub fn generate_graph(grid: &Grid<char>, start: (usize, usize), end: (usize, usize)) -> Graph<(usize, usize)> {
+    let poi = points_of_interest(grid, start, end);
+    let mut graph = Graph::new();
+    for &from in &poi {
+        for first in steps(grid, from) {
+            if let Some((to, tiles)) = follow(grid, &poi, from, first) {
+                let (from, to) = (graph.add_node(from), graph.add_node(to));
+                graph.add_edge(from, to, tiles.len());
             }
         }
     }
-
     graph
 }
 
-fn search_part2(graph: &HashMap<(usize, usize), HashMap<(usize, usize), usize>>, seen: &HashSet<(usize, usize)>, start: (usize, usize), end: (usize, usize)) -> isize {
-    if start == end {
-        return 0;
+// the length of the longest hike from the top left to the bottom right, and the tiles on it
+fn longest_hike(grid: &Grid<char>) -> Option<(usize, Vec<Pos>)> {
+    let (start, end) = ((1, 0), (grid.width() - 2, grid.height() - 1));
+    let graph = generate_graph(grid, start, end);
+    let (length, path) = graph.longest_path(graph.id(&start)?, graph.id(&end)?)?;
+
+    // the graph only has the junctions, so walk the trails between them again for the tiles
+    let poi = points_of_interest(grid, start, end);
+    let mut tiles = vec![start];
+    for pair in path.windows(2) {
+        let (from, to) = (*graph.node(pair[0]), *graph.node(pair[1]));
+        let weight = graph.edges(pair[0]).iter().filter(|&&(next, _)| next == pair[1]).map(|&(_, w)| w).max()?;
+        let trail = steps(grid, from)
+            .filter_map(|first| follow(grid, &poi, from, first))
+            .find(|(end, trail)| *end == to && trail.len() == weight)?;
+        tiles.extend(trail.1);
     }
+    Some((length, tiles))
+}
 
-    let mut s = seen.clone();
-    s.insert(start);
+fn no_hike() -> ParseError {
+    ParseError::whole_input(DAY, "there is no way from the start to the end")
+}
 
-    let mut d = isize::MIN;
-    for adj in graph.get(&start).unwrap().keys() {
-        if !s.contains(adj) {
-            let n = search_part2(graph, &s, *adj, end);
-            d = d.max(n + *graph.get(&start).unwrap().get(adj).unwrap() as isize);
+/// The length of the longest hike, going only downhill on slopes.
+pub fn part1(input: String) -> SolveResult {
+    let grid = parse_input(input)?;
+    let (length, path) = longest_hike(&grid).ok_or_else(no_hike)?;
+    let path = path.into_iter().collect::<HashSet<_>>();
+
+    let mut frame = Frame::new(grid.width(), grid.height());
+    for ((x, y), &c) in grid.iter() {
+        if path.contains(&(x, y)) {
+            frame.set(x, y, 'O', Some(Rgb::WHITE));
+        } else if c == '#' {
+            frame.set(x, y, '#', Some(Rgb(96, 96, 96)));
+        } else {
+            frame.set(x, y, c, None);
         }
     }
 
-    d
+    Ok(Solution::new(length).with_frame(frame))
 }
 
-pub(crate) fn part2(input: String) {
-    let grid = parse_input_part2(input);
-    let start = (1, 0);
-    let end = (grid[0].len() - 2, grid.len() - 1);
-    let graph = generate_graph(grid, start, end);
-    println!("{}", search_part2(&graph, &HashSet::new(), start, end));
-}
\ No newline at end of file
+/// The length of the longest hike when slopes are treated as normal paths.
+pub fn part2(input: String) -> SolveResult {
+    let grid = parse_input_part2(input)?;
+    let (length, _) = longest_hike(&grid).ok_or_else(no_hike)?;
+    Ok(Solution::new(length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::check;
+
+    const EXAMPLE: &str = "\
+#.#####################
+#.......#########...###
+#######.#########.#.###
+###.....#.>.>.###.#.###
+###v#####.#v#.###.#.###
+###.>...#.#.#.....#...#
+###v###.#.#.#########.#
+###...#.#.#.......#...#
+#####.#.#.#######.#.###
+#.....#.#.#.......#...#
+#.#####.#.#.#########v#
+#.#...#...#...###...>.#
+#.#.#v#######v###.###v#
+#...#.>.#...>.>.#.###.#
+#####v#.#.###v#.#.###.#
+#.....#...#...#.#.#...#
+#.#########.###.#.#.###
+#...###...#...#...#.###
+###.###.#.###v#####v###
+#...#...#.#.>.>.#.>.###
+#.###.###.#.###.#.#v###
+#.....###...###...#...#
+#####################.#
+";
+
+    #[test]
+    fn part1_example() {
+        check(part1, EXAMPLE, 94);
+    }
+
+    #[test]
+    fn part2_example() {
+        check(part2, EXAMPLE, 154);
+    }
+}
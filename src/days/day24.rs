@@ -1,9 +1,16 @@
 use std::fmt::Debug;
 use std::str::FromStr;
-use z3::{Config, Context, Solver};
+use z3::{Config, Context, SatResult, Solver};
 use z3::ast::{Ast, Int};
+use crate::parse::{self, Line, ParseError};
+use crate::solution::{Solution, SolveResult};
 
-fn intersect_2d(p1: (f64, f64), v1: (f64, f64), p2: (f64, f64), v2: (f64, f64)) -> Option<(f64, f64)> {
+crate::registry::puzzle!(2023, 24, "Never Tell Me The Odds", part1, part2, |input| {
+    parse::line_errors(input, |input| parse_input::<i64>(input.to_string()))
+});
+
+/// Where the lines through `p1` and `p2` in the directions `v1` and `v2` cross, unless they're parallel.
+pub fn intersect_2d(p1: (f64, f64), v1: (f64, f64), p2: (f64, f64), v2: (f64, f64)) -> Option<(f64, f64)> {
     let (px1, py1) = p1;
     let (px2, py2) = p2;
     let (vx1, vy1) = v1;
@@ -28,13 +35,17 @@ fn to_2d(v: (f64, f64, f64)) -> (f64, f64) {
     (v.0, v.1)
 }
 
+/// A hailstone's starting position and velocity.
 #[derive(Copy, Clone, Debug, PartialEq)]
-struct Hailstone<T: Copy + Clone + Debug + PartialEq> {
-    pos: (T, T, T),
-    vel: (T, T, T),
+pub struct Hailstone<T: Copy + Clone + Debug + PartialEq> {
+    /// Where it is at time 0.
+    pub pos: (T, T, T),
+    /// How far it moves each nanosecond.
+    pub vel: (T, T, T),
 }
 
-fn check_intersection_2d(a: Hailstone<f64>, b: Hailstone<f64>, min: f64, max: f64) -> bool {
+/// Whether the paths of two hailstones (ignoring z) cross in the future within the square `min..=max`.
+pub fn check_intersection_2d(a: Hailstone<f64>, b: Hailstone<f64>, min: f64, max: f64) -> bool {
     let p1 = to_2d(a.pos);
     let p2 = to_2d(b.pos);
     let v1 = to_2d(a.vel);
@@ -52,39 +63,64 @@ fn check_intersection_2d(a: Hailstone<f64>, b: Hailstone<f64>, min: f64, max: f6
     }
 }
 
-fn parse_input<T>(input: String) -> Vec<Hailstone<T>>
+fn parse_triple<T>(line: Line, s: &str, what: &str) -> Result<(T, T, T), ParseError>
 where
     T: Copy + Clone + Debug + PartialEq + FromStr,
-    <T as FromStr>::Err: Debug,
 {
-    input.lines().map(|line| {
-        let (p, v) = line.split_once(" @ ").unwrap();
-        let positions = p.split(", ").map(|s| T::from_str(s.trim()).unwrap()).collect::<Vec<_>>();
-        let pos = (positions[0], positions[1], positions[2]);
+    let v = s.split(",").map(|s| line.parse(s, what)).collect::<Result<Vec<T>, _>>()?;
+    match v[..] {
+        [x, y, z] => Ok((x, y, z)),
+        _ => Err(line.error(s, format!("expected 3 {what}s"))),
+    }
+}
 
-        let velocities = v.split(", ").map(|s| T::from_str(s.trim()).unwrap()).collect::<Vec<_>>();
-        let vel = (velocities[0], velocities[1], velocities[2]);
+/// Parses the `px, py, pz @ vx, vy, vz` lines.
+pub fn parse_input<T>(input: String) -> Result<Vec<Hailstone<T>>, ParseError>
+where
+    T: Copy + Clone + Debug + PartialEq + FromStr,
+{
+    parse::lines(DAY, &input).map(|line| {
+        let (p, v) = line.split_once(line.text, "@")?;
+        let pos = parse_triple(line, p, "position")?;
+        let vel = parse_triple(line, v, "velocity")?;
 
-        Hailstone { pos, vel }
-    }).collect::<Vec<_>>()
+        Ok(Hailstone { pos, vel })
+    }).collect()
 }
 
-pub(crate) fn part1(input: String) {
+/// The number of pairs of hailstone paths that cross within the square `min..=max`.
+pub fn intersections_in_area(input: String, min: f64, max: f64) -> SolveResult {
     let mut sum = 0;
-    let hailstones = parse_input(input);
+    // read as whole numbers, like part 2 (and `aoc23 lint`) does, and only then as floats for the geometry
+    let hailstones = parse_input::<i64>(input)?
+        .into_iter()
+        .map(|h| Hailstone {
+            pos: (h.pos.0 as f64, h.pos.1 as f64, h.pos.2 as f64),
+            vel: (h.vel.0 as f64, h.vel.1 as f64, h.vel.2 as f64),
+        })
+        .collect::<Vec<_>>();
     for i in 0..hailstones.len() {
         for j in (i + 1)..hailstones.len() {
-            if check_intersection_2d(hailstones[i], hailstones[j], 200000000000000.0, 400000000000000.0) {
+            if check_intersection_2d(hailstones[i], hailstones[j], min, max) {
                 sum += 1;
             }
         }
     }
 
-    println!("{sum}");
+    Ok(Solution::new(sum))
+}
+
+/// The number of pairs of hailstone paths that cross within the test area.
+pub fn part1(input: String) -> SolveResult {
+    intersections_in_area(input, 200000000000000.0, 400000000000000.0)
 }
 
-pub(crate) fn part2(input: String) {
-    let hailstones = parse_input(input);
+/// The sum of the coordinates of the position to throw a rock from so that it hits every hailstone.
+pub fn part2(input: String) -> SolveResult {
+    let hailstones = parse_input::<i64>(input)?;
+    if hailstones.is_empty() {
+        return Err(ParseError::whole_input(DAY, "there are no hailstones to hit"));
+    }
     let cfg = Config::new();
     let ctx = Context::new(&cfg);
     let solver = Solver::new(&ctx);
@@ -110,10 +146,46 @@ pub(crate) fn part2(input: String) {
         solver.assert(&(&pzn + &vzn * &tn)._eq(&(&pz + &vz * &tn)));
     }
 
-    solver.check();
-    let model = solver.get_model().unwrap();
-    let x = model.get_const_interp(&px).unwrap().as_i64().unwrap();
-    let y = model.get_const_interp(&py).unwrap().as_i64().unwrap();
-    let z = model.get_const_interp(&pz).unwrap().as_i64().unwrap();
-    println!("{}", x + y + z);
+    match solver.check() {
+        SatResult::Sat => {}
+        SatResult::Unsat => return Err(ParseError::whole_input(DAY, "no single throw hits every hailstone")),
+        SatResult::Unknown => return Err(ParseError::whole_input(DAY, "z3 couldn't tell whether a throw hits every hailstone")),
+    }
+    let model = solver.get_model().ok_or_else(|| ParseError::whole_input(DAY, "z3 found a throw but gave no model of it"))?;
+    let coordinate = |c: &Int| model.get_const_interp(c).and_then(|n| n.as_i64());
+    let (Some(x), Some(y), Some(z)) = (coordinate(&px), coordinate(&py), coordinate(&pz)) else {
+        return Err(ParseError::whole_input(DAY, "the throw's position doesn't fit in 64 bits"));
+    };
+    Ok(Solution::new(x + y + z))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::check;
+
+    const EXAMPLE: &str = "\
+19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @  1, -5, -3
+";
+
+    #[test]
+    fn part1_example() {
+        check(|input| intersections_in_area(input, 7.0, 27.0), EXAMPLE, 2);
+    }
+
+    #[test]
+    fn positions_must_be_whole_numbers() {
+        let error = part1("19, 13.5, 30 @ -2, 1, -2\n".to_string()).unwrap_err();
+        assert_eq!(error.to_string(), "day 24, line 1, column 5: invalid position (found `13.5`)");
+        assert_eq!((PUZZLE.check_input)("19, 13.5, 30 @ -2, 1, -2\n"), [error]);
+    }
+
+    #[test]
+    fn part2_example() {
+        check(part2, EXAMPLE, 47);
+    }
 }
@@ -1,98 +1,75 @@
-use std::collections::{HashMap, HashSet};
-use rand::seq::SliceRandom;
-use rand::thread_rng;
+use crate::graph::Graph;
+use crate::parse::{self, ParseError};
+use crate::solution::{Solution, SolveResult};
 
-fn parse_input(input: String) -> HashMap<String, HashMap<String, usize>> {
-    let mut map: HashMap<String, HashMap<String, usize>> = HashMap::new();
-    for line in input.lines() {
-        let (source, t) = line.split_once(": ").unwrap();
-        for target in t.split_whitespace() {
-            if let Some(v) = map.get_mut(source) {
-                v.insert(target.to_string(), 1);
-            } else {
-                map.insert(source.to_string(), HashMap::from([(target.to_string(), 1)]));
-            }
+crate::registry::puzzle!(2023, 25, "Snowverload", part1, part2, |input| {
+    parse::line_errors(input, |input| parse_input(input.to_string()))
+});
 
-            if let Some(v) = map.get_mut(target) {
-                v.insert(source.to_string(), 1);
-            } else {
-                map.insert(target.to_string(), HashMap::from([(source.to_string(), 1)]));
-            }
+/// Parses the wiring diagram into an undirected graph of components.
+pub fn parse_input(input: String) -> Result<Graph<String>, ParseError> {
+    let mut graph = Graph::new();
+    for line in parse::lines(DAY, &input) {
+        let (source, t) = line.split_once(line.text, ": ")?;
+        let source = graph.add_node(source.to_string());
+        for target in t.split_whitespace() {
+            let target = graph.add_node(target.to_string());
+            graph.add_undirected_edge(source, target, 1);
         }
     }
 
-    map
-}
-
-fn contract_edge(g: &mut HashMap<String, HashMap<String, usize>>, s: &String, t: &String) {
-    let nodes = g.get(t).unwrap().iter().map(|(k, v)| (k.to_string(), *v)).collect::<Vec<_>>();
-    for (node, _) in &nodes {
-        let e = g.get_mut(node).unwrap();
-        let n = *e.get(t).unwrap();
-        e.remove(t);
-        if s != node {
-            if !e.contains_key(s) {
-                e.insert(s.to_string(), n);
-            } else {
-                *e.get_mut(s).unwrap() += n;
-            }
-        }
+    if graph.len() < 2 {
+        return Err(ParseError::whole_input(DAY, "the graph needs at least 2 components to be cut"));
     }
 
-    for (node, n) in nodes {
-        if s != &node {
-            let sc = g.get_mut(s).unwrap();
-            if !sc.contains_key(&node) {
-                sc.insert(node, n);
-            } else {
-                *sc.get_mut(&node).unwrap() += n;
-            }
-        }
-    }
-    g.remove(t);
+    Ok(graph)
 }
 
-fn possible_min_cut(mut g: HashMap<String, HashMap<String, usize>>) -> (usize, usize) {
-    let mut merges = HashMap::new();
-    for u in g.keys() {
-        merges.insert(u.to_string(), HashSet::from([u.to_string()]));
-    }
-
-    while g.len() > 2 {
-        let nodes = g.keys().cloned().collect::<Vec<_>>();
-        let s = nodes.choose(&mut thread_rng()).unwrap();
-        let v = g.get(s).unwrap().keys().cloned().collect::<Vec<_>>();
-        let mut v = vec![];
-        for (n, i) in g.get(s).unwrap().iter().map(|(k, v)| (k.to_string(), *v)) {
-            for _ in 0..i {
-                v.push(n.clone());
-            }
-        }
-        let t = v.choose(&mut thread_rng()).unwrap();
-        contract_edge(&mut g, s, t);
-        let m = merges.get(t).unwrap().into_iter().cloned().collect::<Vec<_>>();
-        merges.remove(t);
-        merges.get_mut(s).unwrap().extend(m);
+/// The product of the sizes of the two groups left after cutting the three wires that split the components.
+pub fn part1(input: String) -> SolveResult {
+    let graph = parse_input(input)?;
+    let (cut, group) = graph.min_cut().ok_or_else(|| ParseError::whole_input(DAY, "the components can't be split in two"))?;
+    if cut != 3 {
+        return Err(ParseError::whole_input(DAY, format!("the fewest wires that split the components is {cut}, not 3")));
     }
+    log::debug!("cutting {cut} wires leaves groups of {} and {} components", group.len(), graph.len() - group.len());
+    Ok(Solution::new(group.len() * (graph.len() - group.len())))
+}
 
-    let n = *g.iter().next().unwrap().1.iter().next().unwrap().1;
-    let mut iter = merges.into_iter();
-    let n1 = iter.next().unwrap().1.len();
-    let n2 = iter.next().unwrap().1.len();
-    (n1 * n2, n)
+/// There is no part 2 on Christmas Day.
+pub fn part2(input: String) -> SolveResult {
+    Ok(Solution::unimplemented("no part 2 (Advent of Code puzzles do not have a part 2 on Christmas Day!)"))
 }
 
-pub(crate) fn part1(input: String) {
-    let graph = parse_input(input);
-    loop {
-        let (a, n) = possible_min_cut(graph.clone());
-        if n == 3 {
-            println!("{a}");
-            break;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::check;
+
+    const EXAMPLE: &str = "\
+jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr
+";
+
+    #[test]
+    fn part1_example() {
+        check(part1, EXAMPLE, 54);
     }
-}
 
-pub(crate) fn part2(input: String) {
-    unimplemented!("Advent of Code puzzles do not have a part 2 on Christmas Day!");
+    #[test]
+    fn part1_needs_a_cut_of_three_wires() {
+        let input = "a: b c\nb: c\nc: d\nd: e f\ne: f\n";
+        assert_eq!(part1(input.to_string()).unwrap_err().message, "the fewest wires that split the components is 1, not 3");
+    }
 }
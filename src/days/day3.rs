@@ -1,20 +1,13 @@
 use std::collections::{HashMap, HashSet};
+use crate::grid::Grid;
+use crate::parse::{self, ParseError};
+use crate::solution::{Solution, SolveResult};
 
-fn get_adjacent_positions(x: usize, y: usize, w: usize, h: usize) -> Vec<(usize, usize)> {
-    let mut v = vec![];
+crate::registry::puzzle!(2023, 3, "Gear Ratios", part1, part2, |input| parse::line_errors(input, parse_schematic));
 
-    let min_x = (x - 1) as isize;
-    let min_y = (y - 1) as isize;
-
-    for vy in min_y..=min_y + 2 {
-        for vx in min_x..=min_x + 2 {
-            if vx > 0 && vy > 0 && vx < (w - 1) as isize && vy < (h - 1) as isize && (vx != x as isize || vy != y as isize) {
-                v.push((vx as usize, vy as usize));
-            }
-        }
-    }
-
-    v
+/// Parses the engine schematic: digits, `.` for empty space, and any other punctuation as a symbol.
+pub fn parse_schematic(input: &str) -> Result<Grid<char>, ParseError> {
+    Grid::parse(DAY, input, |c| (c.is_ascii_digit() || c.is_ascii_punctuation()).then_some(c))
 }
 
 fn process_number(sum: &mut i32, tmp_number: &mut i32, adjacent: &mut bool, gear_positions: &mut HashSet<(usize, usize)>, gears: &mut HashMap<(usize, usize), Vec<i32>>) {
@@ -34,10 +27,8 @@ fn process_number(sum: &mut i32, tmp_number: &mut i32, adjacent: &mut bool, gear
     *tmp_number = 0;
 }
 
-fn day3(input: String, part2: bool) {
-    let schematic = input.lines().map(|l| l.chars().collect::<Vec<char>>()).collect::<Vec<Vec<char>>>();
-    let w = schematic[0].len();
-    let h = schematic.len();
+fn day3(input: String, part2: bool) -> SolveResult {
+    let schematic = parse_schematic(&input)?;
 
     let mut tmp_number = 0;
     let mut adjacent = false;
@@ -45,16 +36,16 @@ fn day3(input: String, part2: bool) {
     let mut sum = 0;
     let mut gears: HashMap<(usize, usize), Vec<i32>> = HashMap::new();
 
-    for y in 0..h {
-        for x in 0..w {
-            if schematic[y][x].is_ascii_digit() {
+    for y in 0..schematic.height() {
+        for x in 0..schematic.width() {
+            if schematic[(x, y)].is_ascii_digit() {
                 tmp_number *= 10;
-                tmp_number += (schematic[y][x] as i32) & 0x0f;
+                tmp_number += (schematic[(x, y)] as i32) & 0x0f;
 
-                for (ax, ay) in get_adjacent_positions(x, y, w, h) {
-                    if schematic[ay][ax].is_ascii_punctuation() && schematic[ay][ax] != '.' {
+                for (ax, ay) in schematic.neighbours8((x, y)) {
+                    if schematic[(ax, ay)].is_ascii_punctuation() && schematic[(ax, ay)] != '.' {
                         adjacent = true;
-                        if schematic[ay][ax] == '*' {
+                        if schematic[(ax, ay)] == '*' {
                             gear_positions.insert((ax, ay));
                         }
                     }
@@ -67,22 +58,53 @@ fn day3(input: String, part2: bool) {
     }
 
     if part2 {
-        println!("{}", gears.into_iter().filter_map(|((x, y), v)| {
+        Ok(Solution::new(gears.into_iter().filter_map(|((x, y), v)| {
             if v.len() == 2 {
                 Some(v[0] * v[1])
             } else {
                 None
             }
-        }).sum::<i32>());
+        }).sum::<i32>()))
     } else {
-        println!("{sum}");
+        Ok(Solution::new(sum))
     }
 }
 
-pub(crate) fn part1(input: String) {
-    day3(input, false);
+/// Sums the part numbers (numbers adjacent to a symbol) in the engine schematic.
+pub fn part1(input: String) -> SolveResult {
+    day3(input, false)
+}
+
+/// Sums the gear ratios of every `*` adjacent to exactly two part numbers.
+pub fn part2(input: String) -> SolveResult {
+    day3(input, true)
 }
 
-pub(crate) fn part2(input: String) {
-    day3(input, true);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::check;
+
+    const EXAMPLE: &str = "\
+467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..
+";
+
+    #[test]
+    fn part1_example() {
+        check(part1, EXAMPLE, 4361);
+    }
+
+    #[test]
+    fn part2_example() {
+        check(part2, EXAMPLE, 467835);
+    }
 }
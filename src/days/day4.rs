@@ -1,23 +1,36 @@
 use std::collections::VecDeque;
-use std::str::FromStr;
+use crate::parse::{self, Line, ParseError};
+use crate::solution::{Solution, SolveResult};
 
-fn parse_i32_if_present(s: &str) -> Option<i32> {
-    if s.is_empty() {
-        None
-    } else {
-        Some(i32::from_str(s.trim()).unwrap())
-    }
+crate::registry::puzzle!(2023, 4, "Scratchcards", part1, part2, |input| parse::line_errors(input, parse_cards));
+
+/// A scratchcard: its number, the winning numbers and the numbers you have.
+pub type Card = (i32, Vec<i32>, Vec<i32>);
+
+fn parse_numbers(line: Line, s: &str) -> Result<Vec<i32>, ParseError> {
+    s.split(" ").filter(|s| !s.is_empty()).map(|s| line.parse(s, "number")).collect()
 }
 
-pub(crate) fn part1(input: String) {
-    println!("{}", input.lines().map(|line| {
-        let (card, numbers) = line.split_once(": ").unwrap();
-        let card = i32::from_str(&card.replace("Card ", "").trim()).unwrap();
+/// Parses a `Card <n>: <winning> | <have>` line.
+pub fn parse_card(line: Line) -> Result<Card, ParseError> {
+    let (card, numbers) = line.split_once(line.text, ": ")?;
+    let card = line.parse(line.strip_prefix(card, "Card ")?, "card number")?;
 
-        let (winning, have) = numbers.trim().split_once(" | ").unwrap();
-        let winning: Vec<i32> = winning.split(" ").filter_map(parse_i32_if_present).collect();
-        let have: Vec<i32> = have.split(" ").filter_map(parse_i32_if_present).collect();
+    let (winning, have) = line.split_once(numbers, " | ")?;
+    let winning = parse_numbers(line, winning)?;
+    let have = parse_numbers(line, have)?;
+
+    Ok((card, winning, have))
+}
 
+/// Parses every card in the input.
+pub fn parse_cards(input: &str) -> Result<Vec<Card>, ParseError> {
+    parse::lines(DAY, input).map(parse_card).collect()
+}
+
+/// Sums the points of every card.
+pub fn part1(input: String) -> SolveResult {
+    Ok(Solution::new(parse_cards(&input)?.into_iter().map(|(_, winning, have)| {
         let mut points = 0;
 
         for n in have {
@@ -31,22 +44,12 @@ pub(crate) fn part1(input: String) {
         }
 
         points
-    }).sum::<i32>());
+    }).sum::<i32>()))
 }
 
-type Card = (i32, Vec<i32>, Vec<i32>);
-
-pub(crate) fn part2(input: String) {
-    let cards = input.lines().map(|line| {
-        let (card, numbers) = line.split_once(": ").unwrap();
-        let card = i32::from_str(&card.replace("Card ", "").trim()).unwrap();
-
-        let (winning, have) = numbers.trim().split_once(" | ").unwrap();
-        let winning: Vec<i32> = winning.split(" ").filter_map(parse_i32_if_present).collect();
-        let have: Vec<i32> = have.split(" ").filter_map(parse_i32_if_present).collect();
-
-        (card, winning, have)
-    }).collect::<Vec<Card>>();
+/// Counts the scratchcards you end up with once winning cards have won copies of the ones after them.
+pub fn part2(input: String) -> SolveResult {
+    let cards = parse_cards(&input)?;
 
     let mut queue = VecDeque::from(cards.clone());
     let mut n = queue.len();
@@ -67,5 +70,30 @@ pub(crate) fn part2(input: String) {
         }
     }
 
-    println!("{n}");
+    Ok(Solution::new(n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::check;
+
+    const EXAMPLE: &str = "\
+Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+";
+
+    #[test]
+    fn part1_example() {
+        check(part1, EXAMPLE, 13);
+    }
+
+    #[test]
+    fn part2_example() {
+        check(part2, EXAMPLE, 30);
+    }
 }
@@ -1,13 +1,23 @@
-use std::str::FromStr;
+use crate::parse::{self, ParseError};
+use crate::solution::{Solution, SolveResult};
 
-struct Mapping {
-    in_start: i64,
-    out_start: i64,
-    len: i64,
+crate::registry::puzzle!(2023, 5, "If You Give A Seed A Fertilizer", part1, part2, |input| {
+    parse::errors(get_seeds_maps(input.to_string()))
+});
+
+/// One line of an almanac map: `len` numbers starting at `in_start` map to the numbers starting at `out_start`.
+pub struct Mapping {
+    /// The first source number.
+    pub in_start: i64,
+    /// The first destination number.
+    pub out_start: i64,
+    /// How many numbers are mapped.
+    pub len: i64,
 }
 
 impl Mapping {
-    fn map(&self, input: i64) -> (i64, bool) {
+    /// Maps one number, and whether this mapping covered it.
+    pub fn map(&self, input: i64) -> (i64, bool) {
         if input >= self.in_start && input < self.in_start + self.len {
             (input + (self.out_start - self.in_start), true)
         } else {
@@ -15,7 +25,8 @@ impl Mapping {
         }
     }
 
-    fn map_range(&self, start: i64, len: i64) -> Vec<(i64, i64, bool)> {
+    /// Maps the range `start..start + len`, splitting it into `(start, len, mapped)` pieces.
+    pub fn map_range(&self, start: i64, len: i64) -> Vec<(i64, i64, bool)> {
         let max = start + len;
 
         let overlap_start = i64::max(self.in_start, start);
@@ -37,7 +48,8 @@ impl Mapping {
         }
     }
 
-    fn map_all_range(mappings: &[Self], start: i64, len: i64) -> Vec<(i64, i64)> {
+    /// Maps a range through a whole map (every mapping in it), returning the resulting `(start, len)` ranges.
+    pub fn map_all_range(mappings: &[Self], start: i64, len: i64) -> Vec<(i64, i64)> {
         if mappings.len() == 0 {
             return if len > 0 {
                 vec![(start, len)]
@@ -61,35 +73,54 @@ impl Mapping {
     }
 }
 
-fn get_seeds_maps(input: String) -> (Vec<i64>, Vec<Vec<Mapping>>) {
-    let (seeds, maps) = input.split_once("\n\n").unwrap();
-    let seeds: Vec<i64> = seeds
-        .replace("seeds: ", "")
-        .trim()
-        .split(" ")
-        .map(|s| i64::from_str(s.trim()).unwrap())
-        .collect();
-
-    let maps: Vec<Vec<Mapping>> = maps.split("\n\n").map(|map| {
-        let mut lines: Vec<&str> = map.lines().collect();
-        lines.remove(0);
+/// Parses the almanac into the seed numbers and each of its maps, in order.
+pub fn get_seeds_maps(input: String) -> Result<(Vec<i64>, Vec<Vec<Mapping>>), ParseError> {
+    let sections = parse::sections(DAY, &input);
+    let Some((seeds, maps)) = sections.split_first() else {
+        return Err(ParseError::whole_input(DAY, "input is empty"));
+    };
+    // without the blank line, the first map would be read as more seeds
+    if let Some(line) = seeds.get(1) {
+        return Err(line.error(line.text, "expected a blank line after the seeds"));
+    }
+    let line = seeds[0];
+    let seeds = line.strip_prefix(line.text, "seeds:")?
+        .split_whitespace()
+        .map(|s| line.parse(s, "seed number"))
+        .collect::<Result<Vec<i64>, _>>()?;
+    if seeds.is_empty() {
+        return Err(line.error(line.text, "expected at least one seed number"));
+    }
+
+    let maps = maps.iter().map(|map| {
+        let header = map[0];
+        if !header.text.ends_with(" map:") {
+            return Err(header.error(header.text, "expected a map header like `seed-to-soil map:`"));
+        }
         let mut mappings = vec![];
-        for l in lines {
-            let nums: Vec<i64> = l.split(" ").map(|s| i64::from_str(s.trim()).unwrap()).collect();
+        for l in &map[1..] {
+            let nums = l.text
+                .split_whitespace()
+                .map(|s| l.parse(s, "number"))
+                .collect::<Result<Vec<i64>, _>>()?;
+            if nums.len() != 3 {
+                return Err(l.error(l.text, "expected 3 numbers"));
+            }
             mappings.push(Mapping {
                 in_start: nums[1],
                 out_start: nums[0],
                 len: nums[2],
             });
         }
-        mappings
-    }).collect();
+        Ok(mappings)
+    }).collect::<Result<Vec<_>, _>>()?;
 
-    (seeds, maps)
+    Ok((seeds, maps))
 }
 
-pub(crate) fn part1(input: String) {
-    let (seeds, maps) = get_seeds_maps(input);
+/// The lowest location number of any of the initial seeds.
+pub fn part1(input: String) -> SolveResult {
+    let (seeds, maps) = get_seeds_maps(input)?;
 
     let min = seeds.into_iter().map(|mut seed| {
         for map in &maps {
@@ -102,13 +133,17 @@ pub(crate) fn part1(input: String) {
             }
         }
         seed
-    }).min().unwrap();
+    }).min().unwrap(); // get_seeds_maps makes sure there's a seed
 
-    println!("{min}");
+    Ok(Solution::new(min))
 }
 
-pub(crate) fn part2(input: String) {
-    let (seeds, maps) = get_seeds_maps(input);
+/// The lowest location number when the seed numbers are pairs of ranges.
+pub fn part2(input: String) -> SolveResult {
+    let (seeds, maps) = get_seeds_maps(input)?;
+    if seeds.len() % 2 != 0 {
+        return Err(ParseError::whole_input(DAY, "the seed numbers should be pairs of a start and a length"));
+    }
     let seed_ranges: Vec<(i64, i64)> = seeds.chunks_exact(2).map(|chunk| (chunk[0], chunk[1])).collect();
     let mut ranges: Vec<Vec<(i64, i64)>> = vec![seed_ranges];
 
@@ -120,7 +155,119 @@ pub(crate) fn part2(input: String) {
         ranges.push(mapped);
     }
 
-    let min = ranges.last().unwrap().iter().map(|(s, _)| *s).min().unwrap();
+    let min = ranges
+        .last()
+        .unwrap()
+        .iter()
+        .map(|(s, _)| *s)
+        .min()
+        .ok_or_else(|| ParseError::whole_input(DAY, "every seed range is empty"))?;
+
+    Ok(Solution::new(min))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::Rng;
+    use super::*;
+    use crate::testing::{answer, check, property};
+
+    const EXAMPLE: &str = "\
+seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4
+";
+
+    #[test]
+    fn part1_example() {
+        check(part1, EXAMPLE, 35);
+    }
+
+    #[test]
+    fn part2_example() {
+        check(part2, EXAMPLE, 46);
+    }
 
-    println!("{min}");
+    // random maps whose source ranges don't overlap, as in the puzzle
+    #[test]
+    fn malformed_almanacs() {
+        let error = |input: &str| get_seeds_maps(input.to_string()).err().map(|e| e.to_string());
+        assert_eq!(
+            error("seeds: 79 14\nseed-to-soil map:\n50 98 2\n"),
+            Some("day 5, line 2, column 1: expected a blank line after the seeds (found `seed-to-soil map:`)".to_string()),
+        );
+        assert_eq!(
+            error("seeds: 79 14\n\n50 98 2\n"),
+            Some("day 5, line 3, column 1: expected a map header like `seed-to-soil map:` (found `50 98 2`)".to_string()),
+        );
+        assert_eq!(
+            error("seeds:\n\nseed-to-soil map:\n50 98 2\n"),
+            Some("day 5, line 1, column 1: expected at least one seed number (found `seeds:`)".to_string()),
+        );
+        let odd = part2("seeds: 79 14 55\n\nseed-to-soil map:\n50 98 2\n".to_string()).unwrap_err();
+        assert_eq!(odd.message, "the seed numbers should be pairs of a start and a length");
+    }
+
+    fn random_maps(rng: &mut StdRng) -> String {
+        let mut almanac = String::new();
+        for map in 0..rng.gen_range(1..=4) {
+            almanac.push_str(&format!("\nstep{map}-to-step{} map:\n", map + 1));
+            let mut starts = (0..100).step_by(10).collect::<Vec<i64>>();
+            starts.shuffle(rng);
+            for &start in &starts[..rng.gen_range(1..=4)] {
+                let len = rng.gen_range(1..=10);
+                almanac.push_str(&format!("{} {start} {len}\n", rng.gen_range(0..100)));
+            }
+        }
+        almanac
+    }
+
+    // part 2 maps whole ranges at once, which should give the same minimum as mapping every seed in them one by one
+    #[test]
+    fn range_mapping_matches_seed_by_seed() {
+        property("range_mapping_matches_seed_by_seed", 100, |rng| {
+            let ranges = (0..rng.gen_range(1..=3))
+                .map(|_| (rng.gen_range(0..100), rng.gen_range(1..=20)))
+                .collect::<Vec<(i64, i64)>>();
+            let seed_ranges = ranges.iter().flat_map(|&(start, len)| [start, len]).collect::<Vec<_>>();
+            let seeds = ranges.iter().flat_map(|&(start, len)| start..start + len).collect::<Vec<_>>();
+            let maps = random_maps(rng);
+            let almanac = |seeds: &[i64]| {
+                format!("seeds: {}\n{maps}", seeds.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(" "))
+            };
+            assert_eq!(answer(part2, almanac(&seed_ranges)), answer(part1, almanac(&seeds)));
+        });
+    }
 }
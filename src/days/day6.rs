@@ -1,15 +1,38 @@
 use std::str::FromStr;
+use crate::parse::{self, Line, ParseError};
+use crate::solution::{Solution, SolveResult};
 
-pub(crate) fn part1(input: String) {
-    let lines: Vec<&str> = input.trim().lines().collect();
-    let times: Vec<i32> = lines[0].split_whitespace().filter_map(|s| i32::from_str(s).ok()).collect();
-    let distances: Vec<i32> = lines[1].split_whitespace().filter_map(|s| i32::from_str(s).ok()).collect();
+crate::registry::puzzle!(2023, 6, "Wait For It", part1, part2, |input| parse::errors(parse_races(input)));
+
+// returns the part of the time and distance lines after their `Time:`/`Distance:` labels
+fn parse_lines(input: &str) -> Result<(Line<'_>, &str, Line<'_>, &str), ParseError> {
+    let lines = parse::lines(DAY, input).filter(|l| !l.text.trim().is_empty()).collect::<Vec<_>>();
+    let [time, distance] = lines[..] else {
+        return Err(ParseError::whole_input(DAY, format!("expected 2 lines, found {}", lines.len())));
+    };
+    let times = time.strip_prefix(time.text, "Time:")?;
+    let distances = distance.strip_prefix(distance.text, "Distance:")?;
+    Ok((time, times, distance, distances))
+}
+
+/// Parses the races as separate (time, record distance) pairs.
+pub fn parse_races(input: &str) -> Result<Vec<(i32, i32)>, ParseError> {
+    let (time, times, distance, distances) = parse_lines(input)?;
+    let times = times.split_whitespace().map(|s| time.parse(s, "time")).collect::<Result<Vec<i32>, _>>()?;
+    let distances = distances.split_whitespace().map(|s| distance.parse(s, "distance")).collect::<Result<Vec<i32>, _>>()?;
+    if times.len() != distances.len() {
+        return Err(distance.error(distance.text, format!("expected {} distances to match the times", times.len())));
+    }
+    Ok(times.into_iter().zip(distances).collect())
+}
+
+/// Multiplies together the number of ways to win each race.
+pub fn part1(input: String) -> SolveResult {
+    let races = parse_races(&input)?;
 
     let mut total = 1;
 
-    for i in 0..times.len() {
-        let time = times[i];
-        let distance = distances[i];
+    for (time, distance) in races {
         let mut ways = 0;
         for t in 1..time {
             let d = t * (time - t);
@@ -20,10 +43,12 @@ pub(crate) fn part1(input: String) {
         total *= ways;
     }
 
-    println!("{total}");
+    Ok(Solution::new(total))
 }
 
-fn binsearch(time: i64, distance: i64, from_t: i64, to_t: i64, lb: bool) -> i64 {
+/// Binary searches `from_t..=to_t` for the first (`lb`) or last button hold time that beats `distance` in a race
+/// lasting `time`.
+pub fn binsearch(time: i64, distance: i64, from_t: i64, to_t: i64, lb: bool) -> i64 {
     if to_t - from_t <= 1 {
         return if lb {
             to_t
@@ -50,11 +75,62 @@ fn binsearch(time: i64, distance: i64, from_t: i64, to_t: i64, lb: bool) -> i64
     }
 }
 
-pub(crate) fn part2(input: String) {
-    let lines: Vec<&str> = input.trim().lines().collect();
-    let time = i64::from_str(&lines[0].replace(|c: char| !c.is_ascii_digit(), "")).unwrap();
-    let distance = i64::from_str(&lines[1].replace(|c: char| !c.is_ascii_digit(), "")).unwrap();
+/// The number of ways to win the single race you get by ignoring the spaces between the numbers.
+pub fn part2(input: String) -> SolveResult {
+    let (time_line, time, distance_line, distance) = parse_lines(&input)?;
+    let time = i64::from_str(&time.replace(' ', ""))
+        .map_err(|_| time_line.error(time.trim(), "invalid time"))?;
+    let distance = i64::from_str(&distance.replace(' ', ""))
+        .map_err(|_| distance_line.error(distance.trim(), "invalid distance"))?;
     let lb = binsearch(time, distance, 0, time/2, true);
     let ub = binsearch(time, distance, time/2, time, false);
-    println!("{}", ub - lb + 1);
+    Ok(Solution::new(ub - lb + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+    use super::*;
+    use crate::solution::Answer;
+    use crate::testing::{answer, check, property};
+
+    const EXAMPLE: &str = "\
+Time:      7  15   30
+Distance:  9  40  200
+";
+
+    #[test]
+    fn part1_example() {
+        check(part1, EXAMPLE, 288);
+    }
+
+    #[test]
+    fn part2_example() {
+        check(part2, EXAMPLE, 71503);
+    }
+
+    // part 2 on a single race is the binary search, which should count the same ways to win as part 1's scan
+    #[test]
+    fn binsearch_matches_scan() {
+        property("binsearch_matches_scan", 100, |rng| {
+            let races = (0..rng.gen_range(1..=4)).map(|_| {
+                let time = rng.gen_range(2..=60);
+                (time, rng.gen_range(0..(time / 2) * (time - time / 2)))
+            }).collect::<Vec<(i64, i64)>>();
+
+            let race = |times: &[i64], distances: &[i64]| format!(
+                "Time: {}\nDistance: {}\n",
+                times.iter().map(|t| format!("{t:>3}")).collect::<Vec<_>>().join(" "),
+                distances.iter().map(|d| format!("{d:>3}")).collect::<Vec<_>>().join(" "),
+            );
+            let expected = races.iter().map(|&(time, distance)| {
+                match answer(part2, race(&[time], &[distance])) {
+                    Answer::Int(n) => n,
+                    a => panic!("unexpected answer {a}"),
+                }
+            }).product::<i64>();
+            let (times, distances): (Vec<_>, Vec<_>) = races.into_iter().unzip();
+            assert_eq!(answer(part1, race(&times, &distances)), Answer::Int(expected));
+        });
+    }
 }
@@ -1,21 +1,35 @@
 use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::str::FromStr;
+use crate::parse::{self, Line, ParseError};
+use crate::solution::{Solution, SolveResult};
 use HandType::*;
 
+crate::registry::puzzle!(2023, 7, "Camel Cards", part1, part2, |input| {
+    parse::line_errors(input, |input| parse::lines(DAY, input).try_for_each(|line| parse_hand(line).map(drop)))
+});
+
+/// The type of a Camel Cards hand, strongest first.
 #[derive(Eq, PartialEq, Ord, PartialOrd)]
-enum HandType {
+pub enum HandType {
+    /// All five cards the same.
     FiveKind,
+    /// Four cards the same.
     FourKind,
+    /// Three of one card and two of another.
     FullHouse,
+    /// Three cards the same.
     ThreeKind,
+    /// Two pairs.
     TwoPair,
+    /// One pair.
     OnePair,
+    /// All five cards different.
     HighCard,
 }
 
 impl HandType {
-    fn from_hand(hand: &str, part2: bool) -> Self {
+    /// The type of a five card hand; with `part2`, `J`s are jokers that act as whichever card is best.
+    pub fn from_hand(hand: &str, part2: bool) -> Self {
         assert_eq!(hand.len(), 5);
         let mut cards: HashMap<char, i32> = HashMap::new();
         for card in hand.chars() {
@@ -68,7 +82,8 @@ impl HandType {
     }
 }
 
-fn compare_cards(c1: char, c2: char, joker: u32) -> Ordering {
+/// Compares two cards by rank, with `J` worth `joker`.
+pub fn compare_cards(c1: char, c2: char, joker: u32) -> Ordering {
     fn card_to_u32(c: char, joker: u32) -> u32 {
         match c {
             '0'..='9' => c.to_digit(10).unwrap(),
@@ -84,15 +99,22 @@ fn compare_cards(c1: char, c2: char, joker: u32) -> Ordering {
     card_to_u32(c1, joker).cmp(&card_to_u32(c2, joker))
 }
 
-fn day7(input: String, part2: bool) {
-    let mut hands = input
-        .lines()
-        .map(|l| l
-            .split_once(" ")
-            .map(|(r, l)| (r, i32::from_str(l).unwrap()))
-            .unwrap()
-        )
-        .collect::<Vec<(&str, i32)>>();
+/// Parses a `<hand> <bid>` line.
+pub fn parse_hand(line: Line<'_>) -> Result<(&str, i32), ParseError> {
+    let (hand, bid) = line.split_once(line.text, " ")?;
+    if let Some((i, c)) = hand.char_indices().find(|(_, c)| !"23456789TJQKA".contains(*c)) {
+        return Err(line.error_at(i, &c.to_string(), "invalid card"));
+    }
+    if hand.len() != 5 {
+        return Err(line.error(hand, "a hand must have exactly 5 cards"));
+    }
+    Ok((hand, line.parse(bid, "bid")?))
+}
+
+fn day7(input: String, part2: bool) -> SolveResult {
+    let mut hands = parse::lines(DAY, &input)
+        .map(parse_hand)
+        .collect::<Result<Vec<(&str, i32)>, _>>()?;
 
     let joker_val = if part2 {
         1
@@ -121,17 +143,43 @@ fn day7(input: String, part2: bool) {
         }
     });
 
-    println!("{}", hands
+    Ok(Solution::new(hands
         .into_iter()
         .enumerate()
         .map(|(i, (c, b))| (i as i32 + 1) * b)
-        .sum::<i32>());
+        .sum::<i32>()))
+}
+
+/// The total winnings of every hand, ranked by strength.
+pub fn part1(input: String) -> SolveResult {
+    day7(input, false)
 }
 
-pub(crate) fn part1(input: String) {
-    day7(input, false);
+/// Like [`part1`], but `J`s are jokers.
+pub fn part2(input: String) -> SolveResult {
+    day7(input, true)
 }
 
-pub(crate) fn part2(input: String) {
-    day7(input, true);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::check;
+
+    const EXAMPLE: &str = "\
+32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483
+";
+
+    #[test]
+    fn part1_example() {
+        check(part1, EXAMPLE, 6440);
+    }
+
+    #[test]
+    fn part2_example() {
+        check(part2, EXAMPLE, 5905);
+    }
 }
@@ -1,14 +1,24 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use crate::graph::{Graph, NodeId};
+use crate::number_theory::{self, Cycle};
+use crate::parse::{self, ParseError};
+use crate::solution::{Solution, SolveResult};
 use Instruction::*;
 
+crate::registry::puzzle!(2023, 8, "Haunted Wasteland", part1, part2, |input| parse::errors(parse_input(input.to_string())));
+
+/// One step of the left/right instructions.
 #[derive(Copy, Clone, Debug)]
-enum Instruction {
+pub enum Instruction {
+    /// Go right (`R`).
     Right,
+    /// Go left (`L`).
     Left,
 }
 
 impl Instruction {
-    fn from_char(c: char) -> Option<Self> {
+    /// Parses an `L` or `R`.
+    pub fn from_char(c: char) -> Option<Self> {
         match c {
             'R' => Some(Right),
             'L' => Some(Left),
@@ -17,87 +27,235 @@ impl Instruction {
     }
 }
 
-#[derive(Debug)]
-struct Node {
-    right: String,
-    left: String,
+/// The network of nodes. Every node has two edges: to the node on its left, then to the one on its right.
+pub type Map = Graph<String, ()>;
+
+/// The node `inst` leads to from `node`.
+pub fn follow(nodes: &Map, node: NodeId, inst: Instruction) -> NodeId {
+    match inst {
+        Left => nodes.edges(node)[0].0,
+        Right => nodes.edges(node)[1].0,
+    }
 }
 
-type Map = HashMap<String, Node>;
+/// Parses the instructions and the network, checking every node leads somewhere that exists.
+pub fn parse_input(input: String) -> Result<(Vec<Instruction>, Map), ParseError> {
+    let sections = parse::sections(DAY, &input);
+    let [instructions, nodes] = &sections[..] else {
+        return Err(ParseError::whole_input(DAY, "expected the instructions and the nodes separated by a blank line"));
+    };
 
-fn parse_input(input: String) -> (Vec<Instruction>, Map) {
-    let (instructions, nodes) = input.split_once("\n\n").unwrap();
-    let instructions = instructions.trim().chars().map(|c| Instruction::from_char(c).unwrap()).collect::<Vec<_>>();
-    let nodes = nodes.lines().map(|line| {
-        let l = line.split(|c: char| !c.is_ascii_alphanumeric()).filter(|s| !s.is_empty()).collect::<Vec<_>>();
-        (l[0].to_string(), Node { right: l[2].to_string(), left: l[1].to_string()})
-    }).collect::<Map>();
+    let line = instructions[0];
+    let instructions = line.text.trim().char_indices().map(|(i, c)| {
+        Instruction::from_char(c).ok_or_else(|| line.error_at(i, &c.to_string(), "instructions must be L or R"))
+    }).collect::<Result<Vec<_>, _>>()?;
 
-    (instructions, nodes)
+    let nodes = nodes.iter().map(|line| {
+        let (name, targets) = line.split_once(line.text, " = ")?;
+        let (left, right) = line.split_once(targets, ", ")?;
+        let left = line.strip_prefix(left, "(")?;
+        let right = right.strip_suffix(")").ok_or_else(|| line.error(right, "expected `)`"))?;
+        Ok((line, name, left, right))
+    }).collect::<Result<Vec<_>, ParseError>>()?;
+
+    let mut map = Map::new();
+    for (_, name, _, _) in &nodes {
+        map.add_node(name.to_string());
+    }
+    for (line, name, left, right) in nodes {
+        let node = map.id(name).unwrap();
+        for target in [left, right] {
+            let target = map.id(target).ok_or_else(|| line.error(target, "unknown node"))?;
+            map.add_edge(node, target, ());
+        }
+    }
+
+    Ok((instructions, map))
 }
 
-pub(crate) fn part1(input: String) {
-    let (instructions, nodes) = parse_input(input);
+/// The number of steps from `AAA` to `ZZZ`.
+pub fn part1(input: String) -> SolveResult {
+    let (instructions, nodes) = parse_input(input)?;
+    let Some(start) = nodes.id("AAA") else {
+        return Err(ParseError::whole_input(DAY, "there is no AAA node to start from"));
+    };
+    let Some(goal) = nodes.id("ZZZ") else {
+        return Err(ParseError::whole_input(DAY, "there is no ZZZ node to reach"));
+    };
 
+    // being back at the same node at the same point in the instructions means going round forever without ZZZ
+    let mut seen = HashSet::new();
     let mut count = 0;
-    let mut current_node = "AAA";
-    for inst in instructions.iter().cycle() {
-        current_node = match inst {
-            Right => &nodes[current_node].right,
-            Left => &nodes[current_node].left,
-        };
+    let mut current_node = start;
+    for (i, &inst) in instructions.iter().enumerate().cycle() {
+        if !seen.insert((current_node, i)) {
+            return Err(ParseError::whole_input(DAY, "the path from AAA goes round in a loop that never reaches ZZZ"));
+        }
+        current_node = follow(&nodes, current_node, inst);
         count += 1;
-        if current_node == "ZZZ" {
-            println!("{count}");
+        if current_node == goal {
             break;
         }
     }
+
+    Ok(Solution::new(count))
 }
 
-fn gcd(a: u64, b: u64) -> u64 {
-    if b == 0 {
-        return a;
+/// The times a ghost is on a node ending in `Z`: once each at `before_cycle`, and then forever at `cycles` once it
+/// has fallen into a loop.
+pub struct Ghost {
+    /// The times before the ghost starts going round its loop.
+    pub before_cycle: Vec<u64>,
+    /// The times once it's in the loop, which all repeat with the loop's length.
+    pub cycles: Vec<Cycle>,
+}
+
+impl Ghost {
+    /// Follows the instructions from `start` until the ghost is somewhere it has already been at the same point in
+    /// the instructions, which is where it starts going round in a loop.
+    pub fn new(nodes: &Map, instructions: &[Instruction], start: NodeId) -> Self {
+        let mut seen = HashMap::new();
+        let mut z_times = vec![];
+        let mut node = start;
+        let mut time = 0;
+        loop {
+            let i = time as usize % instructions.len();
+            if let Some(&cycle_start) = seen.get(&(node, i)) {
+                let (before_cycle, in_cycle): (Vec<_>, Vec<_>) = z_times.into_iter().partition(|&t| t < cycle_start);
+                let period = time - cycle_start;
+                return Ghost {
+                    before_cycle,
+                    cycles: in_cycle.into_iter().map(|offset| Cycle { offset, period }).collect(),
+                };
+            }
+            seen.insert((node, i), time);
+
+            if nodes.node(node).ends_with('Z') {
+                z_times.push(time);
+            }
+            node = follow(nodes, node, instructions[i]);
+            time += 1;
+        }
     }
 
-    gcd(b, a.rem_euclid(b))
+    /// Whether the ghost is on a node ending in `Z` after `time` steps.
+    pub fn on_z(&self, time: u64) -> bool {
+        self.before_cycle.contains(&time) || self.cycles.iter().any(|c| c.contains(time))
+    }
 }
 
-fn lcm(a: u64, b: u64) -> u64 {
-    a * b / gcd(a, b)
+/// The number of steps until every node ending in `A` is simultaneously on a node ending in `Z`.
+pub fn part2(input: String) -> SolveResult {
+    let (instructions, nodes) = parse_input(input)?;
+    let ghosts = nodes
+        .ids()
+        .filter(|&id| nodes.node(id).ends_with('A'))
+        .map(|start| Ghost::new(&nodes, &instructions, start))
+        .collect::<Vec<_>>();
+    for ghost in &ghosts {
+        log::debug!("ghost on Z at {:?} before looping, then at {:?}", ghost.before_cycle, ghost.cycles);
+    }
+    if ghosts.is_empty() {
+        return Err(ParseError::whole_input(DAY, "there are no nodes ending in A to start from"));
+    }
+
+    // either they all line up before one of them starts looping, or it's when one Z time from each of their loops
+    // lines up
+    let early = ghosts
+        .iter()
+        .flat_map(|g| g.before_cycle.iter().copied())
+        .filter(|&t| ghosts.iter().all(|g| g.on_z(t)))
+        .min();
+    let mut combinations = vec![vec![]];
+    for ghost in &ghosts {
+        combinations = combinations.into_iter().flat_map(|combination: Vec<Cycle>| {
+            ghost.cycles.iter().map(move |&cycle| [combination.clone(), vec![cycle]].concat())
+        }).collect();
+    }
+    let looping = combinations.iter().filter_map(|cycles| number_theory::first_common_time(cycles)).min();
+
+    let steps = early.or(looping).ok_or_else(|| ParseError::whole_input(DAY, "the ghosts are never all on Z nodes at once"))?;
+    Ok(Solution::new(steps))
 }
 
-pub(crate) fn part2(input: String) {
-    let (instructions, nodes) = parse_input(input);
-
-    let cycle = nodes
-        .keys()
-        .filter_map(|k| if k.ends_with('A') {
-            Some(k.clone())
-        } else {
-            None
-        })
-        .map(|mut node| {
-            let mut first_z = false;
-            let mut cycle_len: u64 = 0;
-            for inst in instructions.iter().cycle() {
-                node = match inst {
-                    Right => nodes[&node].right.clone(),
-                    Left => nodes[&node].left.clone(),
-                };
-                if first_z {
-                    cycle_len += 1;
-                }
-                if node.ends_with('Z') {
-                    if !first_z {
-                        first_z = true;
-                    }
-                    else {
-                        break;
-                    }
-                }
-            }
-            cycle_len
-        }).reduce(lcm).unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::check;
+
+    const EXAMPLE_1: &str = "\
+RL
+
+AAA = (BBB, CCC)
+BBB = (DDD, EEE)
+CCC = (ZZZ, GGG)
+DDD = (DDD, DDD)
+EEE = (EEE, EEE)
+GGG = (GGG, GGG)
+ZZZ = (ZZZ, ZZZ)
+";
+
+    const EXAMPLE_2: &str = "\
+LLR
+
+AAA = (BBB, BBB)
+BBB = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)
+";
+
+    const EXAMPLE_3: &str = "\
+LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)
+";
 
-    println!("{cycle}");
+    #[test]
+    fn part1_example_1() {
+        check(part1, EXAMPLE_1, 2);
+    }
+
+    #[test]
+    fn part1_example_2() {
+        check(part1, EXAMPLE_2, 6);
+    }
+
+    #[test]
+    fn part1_without_a_way_to_zzz() {
+        let error = |input: &str| part1(input.to_string()).unwrap_err().message;
+        assert_eq!(error("L\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\n"), "there is no ZZZ node to reach");
+        assert_eq!(
+            error("LR\n\nAAA = (BBB, ZZZ)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)\n"),
+            "the path from AAA goes round in a loop that never reaches ZZZ",
+        );
+    }
+
+    #[test]
+    fn part2_example() {
+        check(part2, EXAMPLE_3, 6);
+    }
+
+    // the first ghost is on a Z node at 2, 4, 6, ... and the second at 1, 4, 7, ..., so the gaps between their Z
+    // nodes (2 and 3) aren't enough on their own: they first line up at 4, not 6
+    #[test]
+    fn part2_cycles_with_offsets() {
+        let input = "\
+L
+
+11A = (11B, 11B)
+11B = (11Z, 11Z)
+11Z = (11B, 11B)
+22A = (22Z, 22Z)
+22Z = (22B, 22B)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+";
+        check(part2, input, 4);
+    }
 }
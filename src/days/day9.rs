@@ -1,17 +1,28 @@
 use std::collections::VecDeque;
-use std::str::FromStr;
-
-fn parse_histories(input: String) -> Vec<Vec<i32>> {
-    input
-        .lines()
-        .map(|l| l.split_whitespace()
-            .map(|n| i32::from_str(n).unwrap())
-            .collect::<Vec<_>>()
-        )
-        .collect::<Vec<Vec<_>>>()
+use crate::parse::{self, ParseError};
+use crate::solution::{Solution, SolveResult};
+
+crate::registry::puzzle!(2023, 9, "Mirage Maintenance", part1, part2, |input| {
+    parse::line_errors(input, |input| parse_histories(input.to_string()))
+});
+
+/// Parses one history (a sequence of values) per line.
+pub fn parse_histories(input: String) -> Result<Vec<Vec<i32>>, ParseError> {
+    parse::lines(DAY, &input)
+        .map(|l| {
+            let history = l.text.split_whitespace()
+                .map(|n| l.parse(n, "number"))
+                .collect::<Result<Vec<_>, _>>()?;
+            if history.len() < 2 {
+                return Err(l.error(l.text, "a history needs at least 2 values"));
+            }
+            Ok(history)
+        })
+        .collect()
 }
 
-fn process_diffs(history: Vec<i32>) -> Vec<Vec<i32>> {
+/// The history followed by each successive sequence of differences, down to the first constant one.
+pub fn process_diffs(history: Vec<i32>) -> Vec<Vec<i32>> {
     let mut diffs = vec![history];
 
     loop {
@@ -36,9 +47,10 @@ fn process_diffs(history: Vec<i32>) -> Vec<Vec<i32>> {
     diffs
 }
 
-pub(crate) fn part1(input: String) {
-    let histories = parse_histories(input);
-    println!("{}", histories.into_iter().map(|history| {
+/// Sums the extrapolated next value of each history.
+pub fn part1(input: String) -> SolveResult {
+    let histories = parse_histories(input)?;
+    Ok(Solution::new(histories.into_iter().map(|history| {
         let mut diffs = process_diffs(history);
 
         let last = diffs.last_mut().unwrap();
@@ -54,12 +66,13 @@ pub(crate) fn part1(input: String) {
         }
 
         *diffs[0].last().unwrap()
-    }).sum::<i32>());
+    }).sum::<i32>()))
 }
 
-pub(crate) fn part2(input: String) {
-    let histories = parse_histories(input);
-    println!("{}", histories.into_iter().map(|history| {
+/// Sums the extrapolated previous value of each history.
+pub fn part2(input: String) -> SolveResult {
+    let histories = parse_histories(input)?;
+    Ok(Solution::new(histories.into_iter().map(|history| {
         let mut diffs = process_diffs(history)
             .into_iter()
             .map(VecDeque::from)
@@ -77,5 +90,27 @@ pub(crate) fn part2(input: String) {
         }
 
         diffs[0][0]
-    }).sum::<i32>())
+    }).sum::<i32>()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::check;
+
+    const EXAMPLE: &str = "\
+0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45
+";
+
+    #[test]
+    fn part1_example() {
+        check(part1, EXAMPLE, 114);
+    }
+
+    #[test]
+    fn part2_example() {
+        check(part2, EXAMPLE, 2);
+    }
 }
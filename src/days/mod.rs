@@ -1,76 +1,33 @@
-#![allow(unused_variables)]
-
-#[rustfmt::skip]
-pub(crate) mod day1;
-
-#[rustfmt::skip]
-pub(crate) mod day2;
-
-#[rustfmt::skip]
-pub(crate) mod day3;
-
-#[rustfmt::skip]
-pub(crate) mod day4;
-
-#[rustfmt::skip]
-pub(crate) mod day5;
-
-#[rustfmt::skip]
-pub(crate) mod day6;
-
-#[rustfmt::skip]
-pub(crate) mod day7;
-
-#[rustfmt::skip]
-pub(crate) mod day8;
-
-#[rustfmt::skip]
-pub(crate) mod day9;
-
-#[rustfmt::skip]
-pub(crate) mod day10;
-
-#[rustfmt::skip]
-pub(crate) mod day11;
-
-#[rustfmt::skip]
-pub(crate) mod day12;
+//! The 2023 puzzles, one module per day. Each has a `part1` and `part2` that take the raw puzzle input, and
+//! registers itself (year, day, title and parts) as its `PUZZLE`. A new day needs its module adding to the list
+//! below and nothing else.
 
-#[rustfmt::skip]
-pub(crate) mod day13;
-
-#[rustfmt::skip]
-pub(crate) mod day14;
-
-#[rustfmt::skip]
-pub(crate) mod day15;
-
-#[rustfmt::skip]
-pub(crate) mod day16;
-
-#[rustfmt::skip]
-pub(crate) mod day17;
-
-#[rustfmt::skip]
-pub(crate) mod day18;
-
-#[rustfmt::skip]
-pub(crate) mod day19;
-
-#[rustfmt::skip]
-pub(crate) mod day20;
-
-#[rustfmt::skip]
-pub(crate) mod day21;
-
-#[rustfmt::skip]
-pub(crate) mod day22;
-
-#[rustfmt::skip]
-pub(crate) mod day23;
-
-#[rustfmt::skip]
-pub(crate) mod day24;
+#![allow(unused_variables)]
 
-#[rustfmt::skip]
-pub(crate) mod day25;
+crate::registry::calendar!(
+    day1,
+    day2,
+    day3,
+    day4,
+    day5,
+    day6,
+    day7,
+    day8,
+    day9,
+    day10,
+    day11,
+    day12,
+    day13,
+    day14,
+    day15,
+    day16,
+    day17,
+    day18,
+    day19,
+    day20,
+    day21,
+    day22,
+    day23,
+    day24,
+    day25,
+);
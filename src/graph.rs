@@ -0,0 +1,492 @@
+//! Graphs and the searches the puzzles run over them.
+//!
+//! [`Graph`] stores a graph with named nodes explicitly. The searches that don't need a whole graph ([`astar`],
+//! [`dijkstra`], [`bfs`] and [`dfs`]) take a function giving the successors of a state instead, so they also work
+//! on state spaces that are never built in full.
+
+use std::borrow::Borrow;
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::mem;
+use crate::cancel;
+
+/// The id a [`Graph`] gives a node, which is its index in the order nodes were added.
+pub type NodeId = usize;
+
+/// A directed graph with weighted edges. Nodes are interned: each distinct node gets a [`NodeId`] once, and edges
+/// and searches work on ids. An undirected graph stores every edge both ways.
+#[derive(Clone, Debug)]
+pub struct Graph<N, W = usize> {
+    nodes: Vec<N>,
+    ids: HashMap<N, NodeId>,
+    edges: Vec<Vec<(NodeId, W)>>,
+}
+
+impl<N: Clone + Eq + Hash, W> Graph<N, W> {
+    /// An empty graph.
+    pub fn new() -> Self {
+        Graph {
+            nodes: vec![],
+            ids: HashMap::new(),
+            edges: vec![],
+        }
+    }
+
+    /// Adds `node` if it isn't in the graph yet, and returns its id either way.
+    pub fn add_node(&mut self, node: N) -> NodeId {
+        match self.ids.entry(node) {
+            Entry::Occupied(entry) => *entry.get(),
+            Entry::Vacant(entry) => {
+                self.nodes.push(entry.key().clone());
+                self.edges.push(vec![]);
+                *entry.insert(self.nodes.len() - 1)
+            }
+        }
+    }
+
+    /// The id of `node`, if it's in the graph.
+    pub fn id<Q: Eq + Hash + ?Sized>(&self, node: &Q) -> Option<NodeId> where N: Borrow<Q> {
+        self.ids.get(node).copied()
+    }
+
+    /// The node with id `id`.
+    pub fn node(&self, id: NodeId) -> &N {
+        &self.nodes[id]
+    }
+
+    /// The number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Whether there are no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The ids of every node.
+    pub fn ids(&self) -> impl Iterator<Item = NodeId> {
+        0..self.nodes.len()
+    }
+
+    /// Adds an edge from `from` to `to`. Edges are kept in the order they're added, and adding one twice gives two.
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, weight: W) {
+        self.edges[from].push((to, weight));
+    }
+
+    /// The edges leaving `id`, in the order they were added.
+    pub fn edges(&self, id: NodeId) -> &[(NodeId, W)] {
+        &self.edges[id]
+    }
+
+    /// The nodes with an edge to `id`.
+    pub fn predecessors(&self, id: NodeId) -> Vec<NodeId> {
+        self.ids().filter(|&from| self.edges[from].iter().any(|&(to, _)| to == id)).collect()
+    }
+
+    /// The strongly connected components: the groups of nodes that can all reach each other. Every node is in
+    /// exactly one, and they come out in reverse topological order (a component only has edges to ones before it).
+    pub fn strongly_connected_components(&self) -> Vec<Vec<NodeId>> {
+        // Tarjan's algorithm, with an explicit stack of (node, next edge to look at) instead of recursion
+        let mut index = vec![usize::MAX; self.len()];
+        let mut low_link = vec![0; self.len()];
+        let mut on_stack = vec![false; self.len()];
+        let mut stack = vec![];
+        let mut components = vec![];
+        let mut next_index = 0;
+
+        for root in self.ids() {
+            if index[root] != usize::MAX {
+                continue;
+            }
+
+            let mut call_stack = vec![(root, 0)];
+            while let Some(&mut (node, ref mut edge)) = call_stack.last_mut() {
+                if *edge == 0 {
+                    index[node] = next_index;
+                    low_link[node] = next_index;
+                    next_index += 1;
+                    stack.push(node);
+                    on_stack[node] = true;
+                }
+
+                if let Some(&(next, _)) = self.edges[node].get(*edge) {
+                    *edge += 1;
+                    if index[next] == usize::MAX {
+                        call_stack.push((next, 0));
+                    } else if on_stack[next] {
+                        low_link[node] = low_link[node].min(index[next]);
+                    }
+                    continue;
+                }
+
+                call_stack.pop();
+                if let Some(&(parent, _)) = call_stack.last() {
+                    low_link[parent] = low_link[parent].min(low_link[node]);
+                }
+                if low_link[node] == index[node] {
+                    let mut component = vec![];
+                    loop {
+                        let member = stack.pop().unwrap();
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+
+        components
+    }
+}
+
+impl<N: Clone + Eq + Hash, W> Default for Graph<N, W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: Clone + Eq + Hash, W: Clone> Graph<N, W> {
+    /// Adds an edge between `a` and `b` in both directions.
+    pub fn add_undirected_edge(&mut self, a: NodeId, b: NodeId, weight: W) {
+        self.add_edge(a, b, weight.clone());
+        self.add_edge(b, a, weight);
+    }
+}
+
+impl<N: Clone + Eq + Hash> Graph<N, usize> {
+    /// The cheapest path from `from` to `to`, with its cost.
+    pub fn shortest_path(&self, from: NodeId, to: NodeId) -> Option<(usize, Vec<NodeId>)> {
+        dijkstra([from], |&id| self.edges[id].iter().copied(), |&id| id == to)
+    }
+
+    /// The longest path from `from` to `to` that doesn't visit any node twice, with its length. This is NP-hard in
+    /// general, so it's a plain exhaustive search that's only practical on small graphs.
+    pub fn longest_path(&self, from: NodeId, to: NodeId) -> Option<(usize, Vec<NodeId>)> {
+        // `path` is the nodes so far, ending at `node`; the path is only copied when it's the longest yet
+        fn search<N>(
+            graph: &Graph<N, usize>,
+            (node, length): (NodeId, usize),
+            to: NodeId,
+            path: &mut Vec<NodeId>,
+            visited: &mut [bool],
+            longest: &mut Option<(usize, Vec<NodeId>)>,
+        ) {
+            path.push(node);
+            if node == to {
+                if longest.as_ref().is_none_or(|&(best, _)| length > best) {
+                    *longest = Some((length, path.clone()));
+                }
+            } else {
+                cancel::checkpoint();
+                visited[node] = true;
+                for &(next, weight) in &graph.edges[node] {
+                    if !visited[next] {
+                        search(graph, (next, length + weight), to, path, visited, longest);
+                    }
+                }
+                visited[node] = false;
+            }
+            path.pop();
+        }
+
+        let mut longest = None;
+        search(self, (from, 0), to, &mut vec![], &mut vec![false; self.len()], &mut longest);
+        longest
+    }
+
+    /// The minimum cut of an undirected graph (one that stores each edge both ways): the lowest total weight of
+    /// edges that have to be removed to split it in two, and the nodes on one side of the split. `None` if there
+    /// are fewer than two nodes.
+    pub fn min_cut(&self) -> Option<(usize, Vec<NodeId>)> {
+        if self.len() < 2 {
+            return None;
+        }
+
+        // Stoer-Wagner: each phase finds the cut between the last two nodes of a maximum adjacency ordering, then
+        // merges them
+        let mut adjacent = self.ids().map(|id| {
+            let mut weights = HashMap::new();
+            for &(next, weight) in self.edges[id].iter().filter(|&&(next, _)| next != id) {
+                *weights.entry(next).or_insert(0) += weight;
+            }
+            weights
+        }).collect::<Vec<HashMap<NodeId, usize>>>();
+        let mut members = self.ids().map(|id| vec![id]).collect::<Vec<_>>();
+        let mut remaining = self.ids().collect::<Vec<_>>();
+        let mut best: Option<(usize, Vec<NodeId>)> = None;
+
+        while remaining.len() > 1 {
+            cancel::checkpoint();
+            let mut added = vec![false; self.len()];
+            let mut connection = vec![0; self.len()];
+            let mut queue = BinaryHeap::from([(0, remaining[0])]);
+            let mut order = vec![];
+            let mut last_connection = 0;
+            while let Some((weight, node)) = queue.pop() {
+                if added[node] || weight != connection[node] {
+                    continue;
+                }
+                added[node] = true;
+                order.push(node);
+                last_connection = weight;
+                for (&next, &weight) in &adjacent[node] {
+                    if !added[next] {
+                        connection[next] += weight;
+                        queue.push((connection[next], next));
+                    }
+                }
+            }
+
+            if order.len() < remaining.len() {
+                // not everything is connected, so there's a cut that doesn't need any edges removing
+                return Some((0, order.iter().flat_map(|&node| members[node].iter().copied()).collect()));
+            }
+
+            let (s, t) = (order[order.len() - 2], order[order.len() - 1]);
+            if best.as_ref().is_none_or(|&(cut, _)| last_connection < cut) {
+                best = Some((last_connection, members[t].clone()));
+            }
+
+            let merged = mem::take(&mut members[t]);
+            members[s].extend(merged);
+            for (next, weight) in mem::take(&mut adjacent[t]) {
+                adjacent[next].remove(&t);
+                if next != s {
+                    *adjacent[s].entry(next).or_insert(0) += weight;
+                    *adjacent[next].entry(s).or_insert(0) += weight;
+                }
+            }
+            remaining.retain(|&node| node != t);
+        }
+
+        best
+    }
+}
+
+/// The cheapest path from any of `starts` to a state that `is_goal`, with its cost, where `successors` gives the
+/// states reachable from a state and the cost of moving to each. `heuristic` must never overestimate the remaining
+/// cost to a goal.
+pub fn astar<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> usize,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<(usize, Vec<S>)>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, usize)>,
+{
+    // states are numbered as they're found so the queue and the path back can refer to them cheaply
+    let mut states: Vec<S> = vec![];
+    let mut ids = HashMap::new();
+    let mut cost = vec![];
+    let mut previous: Vec<Option<usize>> = vec![];
+    let mut queue = BinaryHeap::new();
+
+    for start in starts {
+        if !ids.contains_key(&start) {
+            ids.insert(start.clone(), states.len());
+            queue.push((Reverse(heuristic(&start)), states.len()));
+            states.push(start);
+            cost.push(0);
+            previous.push(None);
+        }
+    }
+
+    while let Some((Reverse(estimate), id)) = queue.pop() {
+        cancel::checkpoint();
+        if estimate > cost[id] + heuristic(&states[id]) {
+            continue;
+        }
+
+        if is_goal(&states[id]) {
+            let mut path = vec![states[id].clone()];
+            let mut p = previous[id];
+            while let Some(prev) = p {
+                path.push(states[prev].clone());
+                p = previous[prev];
+            }
+            path.reverse();
+            return Some((cost[id], path));
+        }
+
+        for (next, step) in successors(&states[id]) {
+            let next_cost = cost[id] + step;
+            let next_id = match ids.get(&next) {
+                Some(&next_id) if cost[next_id] <= next_cost => continue,
+                Some(&next_id) => next_id,
+                None => {
+                    ids.insert(next.clone(), states.len());
+                    states.push(next);
+                    cost.push(usize::MAX);
+                    previous.push(None);
+                    states.len() - 1
+                }
+            };
+            cost[next_id] = next_cost;
+            previous[next_id] = Some(id);
+            queue.push((Reverse(next_cost + heuristic(&states[next_id])), next_id));
+        }
+    }
+
+    None
+}
+
+/// [`astar`] without a heuristic.
+pub fn dijkstra<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    successors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<(usize, Vec<S>)>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, usize)>,
+{
+    astar(starts, successors, |_| 0, is_goal)
+}
+
+/// The number of steps to every state reachable from `start`, where `successors` gives the states one step away
+/// from a state.
+pub fn bfs<S, I>(start: S, mut successors: impl FnMut(&S) -> I) -> HashMap<S, usize>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut distances = HashMap::from([(start.clone(), 0)]);
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((state, distance)) = queue.pop_front() {
+        cancel::checkpoint();
+        for next in successors(&state) {
+            if let Entry::Vacant(entry) = distances.entry(next.clone()) {
+                entry.insert(distance + 1);
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    distances
+}
+
+/// Every state reachable from `start`, in depth-first order.
+pub fn dfs<S, I>(start: S, mut successors: impl FnMut(&S) -> I) -> Vec<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+    I::IntoIter: DoubleEndedIterator,
+{
+    let mut seen = HashSet::new();
+    let mut order = vec![];
+    let mut stack = vec![start];
+    while let Some(state) = stack.pop() {
+        cancel::checkpoint();
+        if seen.insert(state.clone()) {
+            // pushed in reverse so the first successor is the first one visited
+            stack.extend(successors(&state).into_iter().rev().filter(|next| !seen.contains(next)));
+            order.push(state);
+        }
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a -1-> b -1-> d, a -5-> c -1-> d, d -1-> b
+    fn graph() -> Graph<&'static str> {
+        let mut graph = Graph::new();
+        for (from, to, weight) in [("a", "b", 1), ("b", "d", 1), ("a", "c", 5), ("c", "d", 1), ("d", "b", 1)] {
+            let (from, to) = (graph.add_node(from), graph.add_node(to));
+            graph.add_edge(from, to, weight);
+        }
+        graph
+    }
+
+    #[test]
+    fn nodes_are_interned() {
+        let mut graph = graph();
+        assert_eq!(graph.len(), 4);
+        assert_eq!(graph.add_node("c"), graph.id("c").unwrap());
+        assert_eq!(graph.len(), 4);
+        assert_eq!(graph.node(graph.id("d").unwrap()), &"d");
+        assert_eq!(graph.id("e"), None);
+        assert_eq!(graph.predecessors(graph.id("d").unwrap()).len(), 2);
+    }
+
+    #[test]
+    fn shortest_and_longest_paths() {
+        let graph = graph();
+        let id = |name| graph.id(name).unwrap();
+        assert_eq!(graph.shortest_path(id("a"), id("d")), Some((2, vec![id("a"), id("b"), id("d")])));
+        assert_eq!(graph.shortest_path(id("d"), id("a")), None);
+        assert_eq!(graph.longest_path(id("a"), id("b")), Some((7, vec![id("a"), id("c"), id("d"), id("b")])));
+        assert_eq!(graph.longest_path(id("b"), id("c")), None);
+    }
+
+    #[test]
+    fn astar_agrees_with_bfs_on_a_grid() {
+        let successors = |&(x, y): &(i32, i32)| {
+            [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]
+                .into_iter()
+                .filter(|&(x, y)| (0..10).contains(&x) && (0..10).contains(&y) && !(x == 5 && y < 8))
+        };
+        let distances = bfs((0, 0), successors);
+        let (cost, path) = astar(
+            [(0, 0)],
+            |&p| successors(&p).map(|next| (next, 1)),
+            |&(x, y)| (9 - x).unsigned_abs() as usize + (9 - y).unsigned_abs() as usize,
+            |&p| p == (9, 9),
+        ).unwrap();
+        assert_eq!(cost, distances[&(9, 9)]);
+        assert_eq!(path.len(), cost + 1);
+        assert_eq!(distances.len(), 92);
+    }
+
+    #[test]
+    fn dfs_goes_deep_first() {
+        let graph = graph();
+        let order = dfs(graph.id("a").unwrap(), |&id| graph.edges(id).iter().map(|&(next, _)| next).collect::<Vec<_>>());
+        assert_eq!(order.into_iter().map(|id| *graph.node(id)).collect::<Vec<_>>(), ["a", "b", "d", "c"]);
+    }
+
+    #[test]
+    fn strongly_connected_components() {
+        let graph = graph();
+        let mut components = graph
+            .strongly_connected_components()
+            .into_iter()
+            .map(|c| {
+                let mut names = c.into_iter().map(|id| *graph.node(id)).collect::<Vec<_>>();
+                names.sort();
+                names
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(components.remove(0), ["b", "d"]);
+        components.sort();
+        assert_eq!(components, [["a"], ["c"]]);
+    }
+
+    #[test]
+    fn min_cut_of_two_triangles() {
+        // two triangles joined by a bridge of weight 2, and a weaker one of weight 1
+        let mut graph = Graph::new();
+        for (a, b, weight) in [(0, 1, 3), (1, 2, 3), (2, 0, 3), (3, 4, 3), (4, 5, 3), (5, 3, 3), (0, 3, 2), (2, 5, 1)] {
+            let (a, b) = (graph.add_node(a), graph.add_node(b));
+            graph.add_undirected_edge(a, b, weight);
+        }
+        let (cut, mut side) = graph.min_cut().unwrap();
+        side.sort();
+        assert_eq!(cut, 3);
+        assert!(side == [0, 1, 2] || side == [3, 4, 5]);
+
+        let mut disconnected = Graph::<_, usize>::new();
+        disconnected.add_node("a");
+        disconnected.add_node("b");
+        assert_eq!(disconnected.min_cut(), Some((0, vec![0])));
+    }
+}
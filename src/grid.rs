@@ -0,0 +1,244 @@
+//! A rectangular grid of cells, which is what the map puzzles parse their input into.
+//!
+//! Positions are `(x, y)` with `(0, 0)` at the top left. Cells can be looked up by position directly (which panics
+//! outside the grid, like indexing a `Vec`), with [`Grid::get`] (which returns `None` outside it) or with
+//! [`Grid::get_wrapping`] (for maps that repeat forever in every direction).
+
+use std::fmt::{Display, Formatter};
+use std::ops::{Index, IndexMut};
+use crate::parse::{self, Line, ParseError};
+
+/// The offsets of the four orthogonal neighbours: up, down, left and right.
+pub const ORTHOGONAL: [(isize, isize); 4] = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+
+/// The offsets of all eight neighbours, in reading order.
+pub const ALL_NEIGHBOURS: [(isize, isize); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+/// A rectangular grid of cells.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// A grid made of `rows`, which must all be the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+        assert!(rows.iter().all(|row| row.len() == width), "grid rows must all be the same length");
+        Grid {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    /// Parses the whole input as a character map, mapping each character through `f` (which returns `None` for
+    /// characters that aren't allowed).
+    pub fn parse(day: usize, input: &str, f: impl FnMut(char) -> Option<T>) -> Result<Self, ParseError> {
+        Ok(Self::from_rows(parse::input_grid(day, input, f)?))
+    }
+
+    /// Parses some lines of the input as a character map (see [`Grid::parse`]).
+    pub fn parse_lines(lines: &[Line], f: impl FnMut(char) -> Option<T>) -> Result<Self, ParseError> {
+        Ok(Self::from_rows(parse::char_grid(lines, f)?))
+    }
+
+    /// The width in cells.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height in cells.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether `(x, y)` is inside the grid.
+    pub fn contains(&self, x: isize, y: isize) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+
+    /// The cell at `(x, y)`, or `None` outside the grid.
+    pub fn get(&self, x: isize, y: isize) -> Option<&T> {
+        self.contains(x, y).then(|| &self.cells[y as usize * self.width + x as usize])
+    }
+
+    /// The cell at `(x, y)` for changing, or `None` outside the grid.
+    pub fn get_mut(&mut self, x: isize, y: isize) -> Option<&mut T> {
+        if self.contains(x, y) {
+            Some(&mut self.cells[y as usize * self.width + x as usize])
+        } else {
+            None
+        }
+    }
+
+    /// The cell at `(x, y)` of the infinite map made by repeating the grid in every direction.
+    pub fn get_wrapping(&self, x: isize, y: isize) -> &T {
+        let x = x.rem_euclid(self.width as isize) as usize;
+        let y = y.rem_euclid(self.height as isize) as usize;
+        &self.cells[y * self.width + x]
+    }
+
+    /// The position `offset` away from `pos`, if that's inside the grid.
+    pub fn offset(&self, pos: (usize, usize), offset: (isize, isize)) -> Option<(usize, usize)> {
+        let (x, y) = (pos.0 as isize + offset.0, pos.1 as isize + offset.1);
+        self.contains(x, y).then_some((x as usize, y as usize))
+    }
+
+    /// The orthogonal neighbours of `pos` that are inside the grid.
+    pub fn neighbours4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        ORTHOGONAL.into_iter().filter_map(move |offset| self.offset(pos, offset))
+    }
+
+    /// The orthogonal and diagonal neighbours of `pos` that are inside the grid.
+    pub fn neighbours8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        ALL_NEIGHBOURS.into_iter().filter_map(move |offset| self.offset(pos, offset))
+    }
+
+    /// Row `y`.
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    /// The rows from top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    /// Column `x`, from top to bottom.
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> {
+        (0..self.height).map(move |y| &self[(x, y)])
+    }
+
+    /// Every position in reading order.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Every cell with its position, in reading order.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// The position of the first cell (in reading order) that matches `predicate`.
+    pub fn position(&self, predicate: impl FnMut(&T) -> bool) -> Option<(usize, usize)> {
+        self.cells.iter().position(predicate).map(|i| (i % self.width, i / self.width))
+    }
+
+    /// A grid of the same size with `f` applied to every cell.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// A grid with every cell set to `value`.
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Grid {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// The grid flipped over its main diagonal, so rows become columns.
+    pub fn transpose(&self) -> Self {
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells: (0..self.width).flat_map(|x| self.column(x).cloned()).collect(),
+        }
+    }
+
+    /// The grid turned a quarter turn clockwise, so the left column becomes the top row.
+    pub fn rotate_clockwise(&self) -> Self {
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells: (0..self.width).flat_map(|x| self.column(x).rev().cloned()).collect(),
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
+        assert!(x < self.width && y < self.height, "({x}, {y}) is outside the {}x{} grid", self.width, self.height);
+        &self.cells[y * self.width + x]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
+        assert!(x < self.width && y < self.height, "({x}, {y}) is outside the {}x{} grid", self.width, self.height);
+        &mut self.cells[y * self.width + x]
+    }
+}
+
+// one row per line, as in the puzzle input
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Grid<char> {
+        Grid::parse(0, "abc\ndef\n", Some).unwrap()
+    }
+
+    #[test]
+    fn neighbours_stop_at_the_edges() {
+        let grid = grid();
+        assert_eq!(grid.neighbours4((0, 0)).collect::<Vec<_>>(), [(0, 1), (1, 0)]);
+        assert_eq!(grid.neighbours8((0, 0)).collect::<Vec<_>>(), [(1, 0), (0, 1), (1, 1)]);
+        assert_eq!(grid.neighbours8((1, 1)).count(), 5);
+        assert_eq!(grid.offset((2, 1), (1, 0)), None);
+    }
+
+    #[test]
+    fn bounded_and_wrapping_lookups() {
+        let grid = grid();
+        assert_eq!(grid.get(2, 1), Some(&'f'));
+        assert_eq!(grid.get(-1, 0), None);
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.get_wrapping(-1, 0), &'c');
+        assert_eq!(grid.get_wrapping(4, -3), &'e');
+    }
+
+    #[test]
+    fn transpose_and_rotate() {
+        let grid = grid();
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf\n");
+        assert_eq!(grid.rotate_clockwise().to_string(), "da\neb\nfc\n");
+        let mut rotated = grid.clone();
+        for _ in 0..4 {
+            rotated = rotated.rotate_clockwise();
+        }
+        assert_eq!(rotated, grid);
+    }
+
+    #[test]
+    #[should_panic(expected = "outside the 3x2 grid")]
+    fn indexing_outside_panics() {
+        grid()[(3, 0)];
+    }
+}
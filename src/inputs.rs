@@ -0,0 +1,146 @@
+//! Finding puzzle inputs: the local cache in `inputs/`, arbitrary files, or stdin.
+
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Where cached inputs are kept, relative to the working directory.
+pub const INPUTS_DIR: &str = "inputs";
+
+/// Which of a day's inputs to use.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum InputKind {
+    /// The personal puzzle input.
+    Real,
+    /// The example from the puzzle text.
+    Example,
+}
+
+impl InputKind {
+    /// Every kind of input.
+    pub const ALL: [InputKind; 2] = [InputKind::Real, InputKind::Example];
+}
+
+impl Display for InputKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            InputKind::Real => "real",
+            InputKind::Example => "example",
+        })
+    }
+}
+
+impl FromStr for InputKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "real" => Ok(InputKind::Real),
+            "example" => Ok(InputKind::Example),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Where an input is cached. Real inputs are shared by both parts of a day, but the examples often differ between
+/// part 1 and part 2.
+pub fn local_path(year: i32, day: usize, part: usize, kind: InputKind) -> PathBuf {
+    let file = match kind {
+        InputKind::Real => format!("day{day}.txt"),
+        InputKind::Example => format!("day{day}-part{part}-example.txt"),
+    };
+    [INPUTS_DIR, &year.to_string(), &file].iter().collect()
+}
+
+/// Reads an input from the cache, if it's there.
+pub fn read_local(year: i32, day: usize, part: usize, kind: InputKind) -> Option<String> {
+    fs::read_to_string(local_path(year, day, part, kind)).ok()
+}
+
+/// Saves an input to the cache.
+pub fn store_local(year: i32, day: usize, part: usize, kind: InputKind, input: &str) -> io::Result<()> {
+    let path = local_path(year, day, part, kind);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, input)
+}
+
+/// Where to read an input from.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InputSource {
+    /// The local cache.
+    Local(InputKind),
+    /// Any file.
+    File(PathBuf),
+    /// Standard input.
+    Stdin,
+}
+
+impl Display for InputSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputSource::Local(kind) => write!(f, "{kind}"),
+            InputSource::File(path) => write!(f, "{}", path.display()),
+            InputSource::Stdin => write!(f, "stdin"),
+        }
+    }
+}
+
+impl FromStr for InputSource {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "" => Err(()),
+            "-" => Ok(InputSource::Stdin),
+            s => Ok(InputKind::from_str(s)
+                .map(InputSource::Local)
+                .unwrap_or_else(|_| InputSource::File(PathBuf::from(s)))),
+        }
+    }
+}
+
+/// Why an input couldn't be loaded.
+#[derive(Debug)]
+pub enum InputError {
+    /// The input isn't in the local cache (at the given path).
+    NotCached(PathBuf),
+    /// Reading the input from the named source failed.
+    Io(String, io::Error),
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputError::NotCached(path) => write!(f, "no cached input at {}", path.display()),
+            InputError::Io(source, e) => write!(f, "could not read input from {source}: {e}"),
+        }
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, InputError> {
+    fs::read_to_string(path).map_err(|e| InputError::Io(path.display().to_string(), e))
+}
+
+/// Loads an input. `Local` inputs only ever come from the `inputs/` cache; downloading them is left to the caller.
+pub fn load(year: i32, day: usize, part: usize, source: &InputSource) -> Result<String, InputError> {
+    match source {
+        InputSource::Local(kind) => {
+            let path = local_path(year, day, part, *kind);
+            if path.is_file() {
+                read_file(&path)
+            } else {
+                Err(InputError::NotCached(path))
+            }
+        }
+        InputSource::File(path) => read_file(path),
+        InputSource::Stdin => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input).map_err(|e| InputError::Io("stdin".to_string(), e))?;
+            Ok(input)
+        }
+    }
+}
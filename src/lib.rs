@@ -0,0 +1,32 @@
+//! Solutions to Advent of Code 2023.
+//!
+//! Every day lives in its own module under [`days`], with a `part1` and `part2` entry point that take the raw
+//! puzzle input and return a [`solution::SolveResult`], plus the parser, domain types and algorithms the parts are
+//! built from. Each day registers itself in [`registry`], which is how the runner finds it. The rest of the crate
+//! is the machinery the `aoc23` binary uses to load inputs, run parts and check or time their answers.
+
+#![warn(missing_docs)]
+
+pub mod all;
+pub mod answers;
+pub mod bench;
+pub mod cancel;
+pub mod days;
+pub mod graph;
+pub mod grid;
+pub mod inputs;
+pub mod lint;
+pub mod logging;
+pub mod number_theory;
+pub mod parse;
+pub mod record;
+pub mod registry;
+pub mod render;
+pub mod runner;
+pub mod solution;
+pub mod submit;
+pub mod verify;
+pub mod watch;
+
+#[cfg(test)]
+mod testing;
@@ -0,0 +1,195 @@
+//! Checking a puzzle input for problems without solving it.
+//!
+//! A few checks apply to every input: that it isn't empty, has Unix line endings, ends with a newline (inputs that
+//! don't have often been cut short) and has no stray whitespace or non-ASCII characters. Then the day's own parser
+//! runs over it (see [`Day::check_input`]), which catches ragged grids, unknown characters, missing sections and
+//! fields that should be numbers.
+
+use std::fmt::{Display, Formatter};
+use crate::parse::ParseError;
+use crate::registry::Day;
+
+/// How bad a problem is.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Severity {
+    /// Suspicious, but the day can still be solved.
+    Warning,
+    /// The day can't be solved with this input.
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// Something wrong with an input.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Problem {
+    /// How bad it is.
+    pub severity: Severity,
+    /// 1-based; 0 means it's about the input as a whole.
+    pub line: usize,
+    /// 1-based, in characters.
+    pub column: usize,
+    /// What's wrong.
+    pub message: String,
+}
+
+impl Problem {
+    fn new(severity: Severity, line: usize, column: usize, message: impl Into<String>) -> Self {
+        Problem { severity, line, column, message: message.into() }
+    }
+
+    /// The problem as `<source>:<line>:<column>: <severity>: <message>`, the way compilers report them.
+    pub fn describe(&self, source: &str) -> String {
+        if self.line == 0 {
+            format!("{source}: {}: {}", self.severity, self.message)
+        } else {
+            format!("{source}:{}:{}: {}: {}", self.line, self.column, self.severity, self.message)
+        }
+    }
+}
+
+impl From<ParseError> for Problem {
+    fn from(e: ParseError) -> Self {
+        let mut message = e.message;
+        if !e.text.is_empty() {
+            message += &format!(" (found `{}`)", e.text.escape_debug());
+        }
+        Problem::new(Severity::Error, e.line, e.column, message)
+    }
+}
+
+// the first line (numbered from 1) where `find` finds something, with what it found there (a column) and how many
+// lines it finds something on
+fn first_of(input: &str, mut find: impl FnMut(&str) -> Option<usize>) -> Option<(usize, usize, usize)> {
+    let mut found = input.split('\n').enumerate().filter_map(|(n, line)| find(line).map(|x| (n + 1, x)));
+    let (line, column) = found.next()?;
+    Some((line, column, found.count() + 1))
+}
+
+fn lines_with(count: usize) -> String {
+    if count == 1 {
+        "only this line".to_string()
+    } else {
+        format!("{count} lines")
+    }
+}
+
+/// Every problem found in `input` for `day`, in order of where they are.
+pub fn lint(day: &Day, input: &str) -> Vec<Problem> {
+    if input.trim().is_empty() {
+        return vec![Problem::new(Severity::Error, 0, 0, "input is empty")];
+    }
+
+    let mut problems = vec![];
+    let char_column = |line: &str, byte: usize| line[..byte].chars().count() + 1;
+
+    if let Some((line, column, count)) = first_of(input, |l| l.find('\r').map(|i| char_column(l, i))) {
+        problems.push(Problem::new(
+            Severity::Error,
+            line,
+            column,
+            format!("Windows line ending (`\\r\\n`) on {}; the input should only have `\\n`", lines_with(count)),
+        ));
+    }
+    // the rest is about what's left once the line endings are fixed
+    let unix = input.replace("\r\n", "\n");
+
+    if !unix.ends_with('\n') {
+        let last = unix.lines().count();
+        problems.push(Problem::new(
+            Severity::Warning,
+            last,
+            unix.lines().last().map_or(0, |l| l.chars().count()) + 1,
+            "no newline at the end of the input, so it may have been cut short",
+        ));
+    }
+    let trailing = |l: &str| (l.trim_end() != l).then(|| l.trim_end().chars().count() + 1);
+    if let Some((line, column, count)) = first_of(&unix, trailing) {
+        problems.push(Problem::new(Severity::Warning, line, column, format!("trailing whitespace on {}", lines_with(count))));
+    }
+    let unusual = |l: &str| l.char_indices().find(|&(_, c)| !c.is_ascii() || c.is_ascii_control()).map(|(i, _)| i);
+    if let Some((line, byte, count)) = first_of(&unix, unusual) {
+        let text = unix.split('\n').nth(line - 1).unwrap();
+        let c = text[byte..].chars().next().unwrap();
+        problems.push(Problem::new(
+            Severity::Warning,
+            line,
+            char_column(text, byte),
+            format!("unusual character `{}` on {}", c.escape_debug(), lines_with(count)),
+        ));
+    }
+
+    problems.extend((day.check_input)(&unix).into_iter().map(Problem::from));
+
+    problems.sort_by_key(|p| (p.line, p.column));
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::days::{day10, day13, day2, day3, day6};
+
+    fn messages(day: &Day, input: &str) -> Vec<String> {
+        lint(day, input).iter().map(|p| p.describe("input")).collect::<Vec<_>>()
+    }
+
+    #[test]
+    fn clean_input_has_no_problems() {
+        assert!(lint(&day6::PUZZLE, "Time: 7 15\nDistance: 9 40\n").is_empty());
+    }
+
+    #[test]
+    fn windows_line_endings_are_reported_once_and_then_ignored() {
+        assert_eq!(messages(&day6::PUZZLE, "Time: 7 15\r\nDistance: 9 40\r\n"), [
+            "input:1:11: error: Windows line ending (`\\r\\n`) on 2 lines; the input should only have `\\n`",
+        ]);
+    }
+
+    #[test]
+    fn truncated_input() {
+        assert_eq!(messages(&day6::PUZZLE, "Time: 7 15\nDistance: 9"), [
+            "input:2:1: error: expected 2 distances to match the times (found `Distance: 9`)",
+            "input:2:12: warning: no newline at the end of the input, so it may have been cut short",
+        ]);
+    }
+
+    #[test]
+    fn parser_problems_have_positions() {
+        assert_eq!(messages(&day10::PUZZLE, "..\n..\n"), [
+            "input: error: grid does not contain a starting position (S)",
+        ]);
+        assert_eq!(messages(&day13::PUZZLE, "#.#\n#.\n"), [
+            "input:2:3: error: row is 2 characters wide, but the first row is 3",
+        ]);
+        assert_eq!(messages(&day6::PUZZLE, "Time: 7 x\nDistance: 9 40 \n"), [
+            "input:1:9: error: invalid time (found `x`)",
+            "input:2:15: warning: trailing whitespace on only this line",
+        ]);
+        assert_eq!(messages(&day6::PUZZLE, ""), ["input: error: input is empty"]);
+    }
+
+    #[test]
+    fn every_bad_line_is_reported() {
+        assert_eq!(messages(&day2::PUZZLE, "Game 1: 3 blue\nGame x: 1 red\nGame 3: 2 green\nGame 4: 1 red; blue\n"), [
+            "input:2:6: error: invalid game id (found `x`)",
+            "input:4:16: error: expected `<count> <colour>` (found `blue`)",
+        ]);
+        assert_eq!(messages(&day3::PUZZLE, "467..114..\n..35..a633\n617*...~.x\n"), [
+            "input:2:7: error: unexpected character (found `a`)",
+            "input:3:10: error: unexpected character (found `x`)",
+        ]);
+        // the missing start only counts once every row is fine
+        assert_eq!(messages(&day10::PUZZLE, "..x\n.x.\n"), [
+            "input:1:3: error: unexpected character (found `x`)",
+            "input:2:2: error: unexpected character (found `x`)",
+        ]);
+    }
+}
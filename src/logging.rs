@@ -0,0 +1,71 @@
+//! Progress messages, intermediate values and debug dumps from the solvers.
+//!
+//! Solvers log through the [`log`] macros (`log::debug!` and friends) rather than printing, so that by default
+//! the only thing on stdout is the answer. The binary installs [`StderrLogger`] with the level chosen on the command
+//! line or in the [`LOG_ENV`] environment variable; when nothing is installed (in tests, or when the crate is used
+//! as a library without a logger) the messages cost next to nothing.
+
+use std::str::FromStr;
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// The environment variable that sets the log level (`error`, `warn`, `info`, `debug`, `trace` or `off`).
+pub const LOG_ENV: &str = "AOC23_LOG";
+
+/// The level used when none is given: only warnings and errors.
+pub const DEFAULT_LEVEL: LevelFilter = LevelFilter::Warn;
+
+/// Writes log messages to stderr, one per line, tagged with their level and the module they came from.
+#[derive(Debug)]
+pub struct StderrLogger;
+
+static LOGGER: StderrLogger = StderrLogger;
+
+impl StderrLogger {
+    /// Installs the logger and shows messages up to `level`. Only the first call has any effect.
+    pub fn init(level: LevelFilter) {
+        if log::set_logger(&LOGGER).is_ok() {
+            log::set_max_level(level);
+        }
+    }
+}
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("{}", format_record(record));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+// `aoc23::days::day22` is just `day22` in the output
+fn format_record(record: &Record) -> String {
+    let source = record.target().rsplit("::").next().unwrap_or_default();
+    format!("{:<5} {source}: {}", record.level().as_str().to_lowercase(), record.args())
+}
+
+/// The level set in [`LOG_ENV`], if it's set to a valid level.
+pub fn level_from_env() -> Option<LevelFilter> {
+    std::env::var(LOG_ENV).ok().and_then(|s| LevelFilter::from_str(&s).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use log::Level;
+    use super::*;
+
+    #[test]
+    fn records_name_their_level_and_module() {
+        let record = Record::builder()
+            .args(format_args!("processing brick {}/{}", 1, 5))
+            .level(Level::Debug)
+            .target("aoc23::days::day22")
+            .build();
+        assert_eq!(format_record(&record), "debug day22: processing brick 1/5");
+    }
+}
@@ -1,35 +1,756 @@
-mod days;
-
-use days::*;
-
-static DAYS: [[fn(String); 2]; 25] = [
-    [day1::part1, day1::part2],
-    [day2::part1, day2::part2],
-    [day3::part1, day3::part2],
-    [day4::part1, day4::part2],
-    [day5::part1, day5::part2],
-    [day6::part1, day6::part2],
-    [day7::part1, day7::part2],
-    [day8::part1, day8::part2],
-    [day9::part1, day9::part2],
-    [day10::part1, day10::part2],
-    [day11::part1, day11::part2],
-    [day12::part1, day12::part2],
-    [day13::part1, day13::part2],
-    [day14::part1, day14::part2],
-    [day15::part1, day15::part2],
-    [day16::part1, day16::part2],
-    [day17::part1, day17::part2],
-    [day18::part1, day18::part2],
-    [day19::part1, day19::part2],
-    [day20::part1, day20::part2],
-    [day21::part1, day21::part2],
-    [day22::part1, day22::part2],
-    [day23::part1, day23::part2],
-    [day24::part1, day24::part2],
-    [day25::part1, day25::part2],
-];
-
-fn main() {
-    aoc_boilerplate::run(2023, DAYS);
+use std::fs::{self, File};
+use std::io;
+use std::panic;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use aoc23::{all, answers, bench, inputs, lint, record, registry, render, runner, submit, verify, watch};
+use aoc23::all::AllOptions;
+use aoc23::answers::Answers;
+use aoc23::bench::{BenchOptions, OutputFormat};
+use aoc23::days::day1::{self, Vocabulary};
+use aoc23::days::day2::{self, Cubes};
+use aoc23::inputs::{InputError, InputKind, InputSource};
+use aoc23::lint::Severity;
+use aoc23::logging::{self, StderrLogger};
+use aoc23::record::Record;
+use aoc23::registry::Day;
+use aoc23::render::{RenderFormat, RenderOptions};
+use aoc23::runner::Failure;
+use aoc23::solution::{Answer, Solution, SolveResult};
+use aoc23::submit::{Client, Guesses, Verdict};
+use log::LevelFilter;
+
+// a registered day and one of its parts
+type Part = (&'static Day, usize);
+
+fn parse_part(s: &str, year: i32) -> Option<Part> {
+    let (day, part) = s.split_once(':')?;
+    let (day, part) = (usize::from_str(day).ok()?, usize::from_str(part).ok()?);
+    let day = registry::find(year, day)?;
+    (1..=2).contains(&part).then_some((day, part))
+}
+
+// `--year <year>` can go anywhere on the command line and picks the year the rest of it refers to, which is the
+// latest registered one if it isn't given
+fn take_year(args: &mut Vec<String>) -> Result<Option<i32>, String> {
+    let Some(i) = args.iter().position(|a| a == "--year") else {
+        return Ok(None);
+    };
+    let year = args.get(i + 1).ok_or("missing value for --year")?;
+    let year = i32::from_str(year).map_err(|_| format!("invalid year `{year}`"))?;
+    if !registry::years().contains(&year) {
+        return Err(format!("no days are registered for {year}"));
+    }
+    args.drain(i..i + 2);
+    Ok(Some(year))
+}
+
+// `--log <level>`, `-v`/`--verbose` (debug) and `-q`/`--quiet` (errors only) can also go anywhere, and override
+// AOC23_LOG
+fn take_log_level(args: &mut Vec<String>) -> Result<Option<LevelFilter>, String> {
+    let mut level = None;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "-v" | "--verbose" => level = Some(LevelFilter::Debug),
+            "-q" | "--quiet" => level = Some(LevelFilter::Error),
+            "--log" => {
+                let value = args.get(i + 1).ok_or("missing value for --log")?;
+                level = Some(LevelFilter::from_str(value).map_err(|_| "log level must be error, warn, info, debug, trace or off")?);
+                args.remove(i);
+            }
+            _ => {
+                i += 1;
+                continue;
+            }
+        }
+        args.remove(i);
+    }
+    Ok(level)
+}
+
+const USAGE: &str = "usage: aoc23 <day>:<part> [real|example|<file>|-] [--year <year>] [--log <level>|-v|-q] [--timeout <secs>] [--format text|json] [--watch] [--bag <cubes>|--bag-file <file>] [--offline] [--render ansi|none|ppm|svg] [--render-to <file>]";
+
+// "1 error", "2 errors"
+fn count(n: usize, noun: &str) -> String {
+    format!("{n} {noun}{}", if n == 1 { "" } else { "s" })
+}
+
+fn run_lint(args: &[String], year: i32) -> ExitCode {
+    const LINT_USAGE: &str = "usage: aoc23 lint <day> [real|example|<file>|-]";
+    let Some(day) = args.first().and_then(|s| usize::from_str(s).ok()).and_then(|d| registry::find(year, d)) else {
+        eprintln!("missing or invalid <day>");
+        eprintln!("{LINT_USAGE}");
+        return ExitCode::FAILURE;
+    };
+    let source = match args.get(1).map(|s| InputSource::from_str(s)) {
+        Some(Ok(source)) => source,
+        None => InputSource::Local(InputKind::Real),
+        Some(Err(())) => {
+            eprintln!("invalid input `{}`", args[1]);
+            eprintln!("{LINT_USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let input = match inputs::load(day.year, day.day, 1, &source) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let problems = lint::lint(day, &input);
+    for problem in &problems {
+        println!("{}", problem.describe(&source.to_string()));
+    }
+
+    let errors = problems.iter().filter(|p| p.severity == Severity::Error).count();
+    if problems.is_empty() {
+        println!("no problems found");
+    } else {
+        let warnings = problems.len() - errors;
+        println!("{}, {}", count(errors, "error"), count(warnings, "warning"));
+    }
+    if errors == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+// day 2's bag, from `--bag <cubes>` or `--bag-file <file>`
+fn parse_bag(option: &str, value: &str) -> Result<Cubes, String> {
+    if option == "--bag" {
+        return Cubes::from_str(value).map_err(|e| format!("invalid bag: {e}"));
+    }
+    let cubes = fs::read_to_string(value).map_err(|e| format!("could not read {value}: {e}"))?;
+    Cubes::from_str(&cubes).map_err(|e| format!("invalid bag in {value}: {e}"))
+}
+
+fn parse_query_args(args: &[String], year: i32) -> Result<(InputSource, Cubes, Option<usize>, bool), String> {
+    let mut positional = vec![];
+    let mut bag = None;
+    let mut at_least = None;
+    let mut csv = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().ok_or_else(|| format!("missing value for {arg}"));
+        match arg.as_str() {
+            "--bag" | "--bag-file" => bag = Some(parse_bag(arg, value()?)?),
+            "--at-least" => at_least = Some(usize::from_str(value()?).map_err(|_| "invalid game count")?),
+            "--csv" => csv = true,
+            s if s.starts_with("--") => return Err(format!("unknown option `{s}`")),
+            _ => positional.push(arg),
+        }
+    }
+
+    let day = positional.first().and_then(|s| usize::from_str(s).ok()).and_then(|d| registry::find(year, d));
+    let day = day.ok_or("missing or invalid <day>")?;
+    if (day.year, day.day) != (day2::PUZZLE.year, day2::PUZZLE.day) {
+        return Err("only day 2 of 2023 can be queried".to_string());
+    }
+    let source = match positional.get(1) {
+        Some(s) => InputSource::from_str(s).map_err(|_| format!("invalid input `{s}`"))?,
+        None => InputSource::Local(InputKind::Real),
+    };
+    // the smallest bag is searched for from nothing, so a bag to start from would be ignored
+    if at_least.is_some() && bag.is_some() {
+        return Err("--at-least finds its own bag, so it can't be given one with --bag or --bag-file".to_string());
+    }
+    Ok((source, bag.unwrap_or_else(day2::default_bag), at_least, csv))
+}
+
+// only day 2 can be queried so far: what each game needs of the bag, or the smallest bag for some number of games
+fn run_query(args: &[String], year: i32) -> ExitCode {
+    const QUERY_USAGE: &str =
+        "usage: aoc23 query 2 [real|example|<file>|-] [--bag <cubes>|--bag-file <file>|--at-least <games>] [--csv]";
+    let (source, bag, at_least, csv) = match parse_query_args(args, year) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!("{QUERY_USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let games = match inputs::load(day2::PUZZLE.year, day2::PUZZLE.day, 1, &source) {
+        Ok(input) => day2::parse_games(&input).map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    let games = match games {
+        Ok(games) => games,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Some(k) = at_least {
+        let Some(smallest) = day2::smallest_bag_for(&games, k) else {
+            eprintln!("there are only {} games", games.len());
+            return ExitCode::FAILURE;
+        };
+        let possible = day2::possible_games(&games, &smallest);
+        if csv {
+            print!("{}", day2::reports_to_csv(&day2::reports(&games, &smallest), &smallest));
+        } else {
+            println!("smallest bag for {k} games: {smallest}");
+            println!("possible games: {}", possible.iter().map(u32::to_string).collect::<Vec<_>>().join(", "));
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    let reports = day2::reports(&games, &bag);
+    if csv {
+        print!("{}", day2::reports_to_csv(&reports, &bag));
+        return ExitCode::SUCCESS;
+    }
+    println!("bag: {bag}");
+    println!("{:>4}  {:<8}  {:>8}  {:<9}  minimal bag", "game", "possible", "power", "limit");
+    for report in &reports {
+        println!(
+            "{:>4}  {:<8}  {:>8}  {:<9}  {}",
+            report.id,
+            if report.possible { "yes" } else { "no" },
+            report.power,
+            report.limiting_colour.as_deref().unwrap_or("-"),
+            report.minimal_bag,
+        );
+    }
+    let possible = reports.iter().filter(|r| r.possible).count();
+    println!("{possible} of {} games possible", reports.len());
+    ExitCode::SUCCESS
+}
+
+// only day 1 can explain itself so far
+fn run_explain(args: &[String], year: i32) -> ExitCode {
+    const EXPLAIN_USAGE: &str = "usage: aoc23 explain 1 [real|example|<file>|-] [--disagreements]";
+    let only_disagreements = args.iter().any(|a| a == "--disagreements");
+    let args = args.iter().filter(|a| *a != "--disagreements").collect::<Vec<_>>();
+    let Some(day) = args.first().and_then(|s| usize::from_str(s).ok()).and_then(|d| registry::find(year, d)) else {
+        eprintln!("missing or invalid <day>");
+        eprintln!("{EXPLAIN_USAGE}");
+        return ExitCode::FAILURE;
+    };
+    if (day.year, day.day) != (day1::PUZZLE.year, day1::PUZZLE.day) {
+        eprintln!("only day 1 of 2023 has an explain mode");
+        return ExitCode::FAILURE;
+    }
+    let source = match args.get(1).map(|s| InputSource::from_str(s)) {
+        Some(Ok(source)) => source,
+        None => InputSource::Local(InputKind::Real),
+        Some(Err(())) => {
+            eprintln!("invalid input `{}`", args[1]);
+            eprintln!("{EXPLAIN_USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // part 2's example is the one with spelled digits
+    let input = match inputs::load(day.year, day.day, 2, &source) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    for explanation in day1::explain(&input, only_disagreements) {
+        println!("{explanation}");
+    }
+    ExitCode::SUCCESS
+}
+
+// only day 1 can be streamed so far
+fn run_stream(args: &[String], year: i32) -> ExitCode {
+    const STREAM_USAGE: &str = "usage: aoc23 stream 1:<part> [<file>|-]";
+    let Some((day, part)) = args.first().and_then(|s| parse_part(s, year)) else {
+        eprintln!("missing or invalid <day>:<part>");
+        eprintln!("{STREAM_USAGE}");
+        return ExitCode::FAILURE;
+    };
+    if (day.year, day.day) != (day1::PUZZLE.year, day1::PUZZLE.day) {
+        eprintln!("only day 1 of 2023 can be streamed");
+        return ExitCode::FAILURE;
+    }
+
+    let vocabulary = if part == 1 { Vocabulary::digits() } else { Vocabulary::english() };
+    let sum = match args.get(1).map(String::as_str) {
+        None | Some("-") => day1::calibration_sum_streaming(io::stdin().lock(), &vocabulary),
+        Some(path) => File::open(path).and_then(|file| day1::calibration_sum_streaming(file, &vocabulary)),
+    };
+    match sum {
+        Ok(sum) => {
+            println!("{sum}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_verify(args: &[String], year: i32) -> ExitCode {
+    let allow_missing = args.iter().any(|a| a == "--allow-missing");
+    let mut paths = args.iter().filter(|a| *a != "--allow-missing").map(PathBuf::from).collect::<Vec<_>>();
+    if paths.is_empty() {
+        paths = vec![PathBuf::from(answers::EXAMPLE_ANSWERS_FILE), PathBuf::from(answers::ANSWERS_FILE)];
+    }
+
+    // without its answers, every part would just show as missing and verify would still pass, so a file that isn't
+    // there has to be asked for
+    let mut answers = Answers::default();
+    for path in paths {
+        match Answers::load(&path) {
+            Ok(loaded) => answers.extend(loaded),
+            Err(e) if e.kind() == io::ErrorKind::NotFound && allow_missing => {
+                log::warn!("no answers file at {}, so carrying on without it", path.display());
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                eprintln!("no answers file at {} (pass --allow-missing to verify without it)", path.display());
+                return ExitCode::FAILURE;
+            }
+            Err(e) => {
+                eprintln!("could not read {}: {e}", path.display());
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    if verify::verify(&registry::year(year), &answers) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn run_submit(args: &[String], year: i32) -> ExitCode {
+    const SUBMIT_USAGE: &str = "usage: aoc23 submit <day>:<part> [real|<file>]";
+    let Some((day, part)) = args.first().and_then(|s| parse_part(s, year)) else {
+        eprintln!("missing or invalid <day>:<part>");
+        eprintln!("{SUBMIT_USAGE}");
+        return ExitCode::FAILURE;
+    };
+    let source = match args.get(1).map(|s| InputSource::from_str(s)) {
+        None => InputSource::Local(InputKind::Real),
+        Some(Ok(source @ (InputSource::Local(InputKind::Real) | InputSource::File(_)))) => source,
+        Some(_) => {
+            eprintln!("only a real input can be submitted, not `{}`", args[1]);
+            eprintln!("{SUBMIT_USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let Ok(session) = std::env::var("AOC_SESSION") else {
+        eprintln!("submitting needs your session token in AOC_SESSION");
+        return ExitCode::FAILURE;
+    };
+
+    let input = match inputs::load(day.year, day.day, part, &source) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let answer = match day.part(part)(input) {
+        Ok(Solution { answer: Answer::Unimplemented(reason), .. }) => {
+            eprintln!("nothing to submit: {reason}");
+            return ExitCode::FAILURE;
+        }
+        Ok(solution) => solution.answer.to_string(),
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let path = PathBuf::from(submit::GUESSES_FILE);
+    let mut guesses = match Guesses::load(&path) {
+        Ok(guesses) => guesses,
+        Err(e) => {
+            eprintln!("could not read {}: {e}", path.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    // AOC_SERVER points submissions somewhere else, like a local mock of the site
+    let server = std::env::var("AOC_SERVER").unwrap_or_else(|_| submit::DEFAULT_SERVER.to_string());
+    let verdict = Client::new(&server, &session).submit(&mut guesses, (day.year, day.day, part), &answer);
+    if let Err(e) = guesses.save(&path) {
+        log::warn!("could not save {}: {e}", path.display());
+    }
+
+    match verdict {
+        Ok(verdict) => {
+            println!("{answer}: {verdict}");
+            if verdict == Verdict::Correct {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Err(e) => {
+            eprintln!("{answer}: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn list(year: Option<i32>) {
+    println!("{:>4}  {:>3}  title", "year", "day");
+    for day in registry::all().into_iter().filter(|d| year.is_none_or(|y| d.year == y)) {
+        println!("{:>4}  {:>3}  {}", day.year, day.day, day.title);
+    }
+}
+
+fn parse_bench_args(args: &[String], year: i32) -> Result<(Vec<Part>, BenchOptions), String> {
+    let mut parts = None;
+    let mut options = BenchOptions::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().ok_or_else(|| format!("missing value for {arg}"));
+        match arg.as_str() {
+            "--warmup" => options.warmup = usize::from_str(value()?).map_err(|_| "invalid warmup count")?,
+            "--runs" => options.runs = usize::from_str(value()?).map_err(|_| "invalid run count")?,
+            "--input" => options.kind = InputKind::from_str(value()?).map_err(|_| "input must be real or example")?,
+            "--format" => options.format = match value()?.as_str() {
+                "table" => OutputFormat::Table,
+                "json" => OutputFormat::Json,
+                _ => return Err("format must be table or json".to_string()),
+            },
+            "all" => parts = Some(registry::year(year).into_iter().flat_map(|d| [(d, 1), (d, 2)]).collect()),
+            s => parts = Some(vec![parse_part(s, year).ok_or_else(|| format!("invalid part `{s}`"))?]),
+        }
+    }
+
+    if options.runs == 0 {
+        return Err("need at least one timed run".to_string());
+    }
+
+    Ok((parts.ok_or("nothing to benchmark")?, options))
+}
+
+fn parse_timeout(value: Option<&String>) -> Result<Duration, String> {
+    let secs = value.ok_or("missing value for --timeout")?;
+    f64::from_str(secs)
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .filter(|timeout| !timeout.is_zero())
+        .ok_or_else(|| format!("invalid timeout `{secs}`"))
+}
+
+fn parse_all_args(args: &[String]) -> Result<AllOptions, String> {
+    let mut options = AllOptions::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--jobs" => {
+                let jobs = iter.next().ok_or("missing value for --jobs")?;
+                options.jobs = usize::from_str(jobs).ok().filter(|&n| n > 0).ok_or("invalid job count")?;
+            }
+            "--diagnostics" => options.diagnostics = true,
+            "--timeout" => options.timeout = Some(parse_timeout(iter.next())?),
+            "--format" => options.format = match iter.next().map(String::as_str) {
+                Some("table") => OutputFormat::Table,
+                Some("json") => OutputFormat::Json,
+                _ => return Err("format must be table or json".to_string()),
+            },
+            s => options.kind = InputKind::from_str(s).map_err(|_| format!("unknown argument `{s}`"))?,
+        }
+    }
+
+    Ok(options)
+}
+
+struct RunArgs {
+    day: &'static Day,
+    part: usize,
+    source: InputSource,
+    offline: bool,
+    render: RenderOptions,
+    timeout: Option<Duration>,
+    json: bool,
+    watch: bool,
+    // day 2's bag, from `--bag` or `--bag-file`
+    bag: Option<Cubes>,
+}
+
+fn parse_run_args(args: &[String], year: i32) -> Result<RunArgs, String> {
+    let mut positional = vec![];
+    let mut offline = std::env::var_os("AOC_SESSION").is_none();
+    let mut render = RenderOptions::default();
+    let mut timeout = None;
+    let mut json = false;
+    let mut watch = false;
+    let mut bag = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().ok_or_else(|| format!("missing value for {arg}"));
+        match arg.as_str() {
+            "--offline" => offline = true,
+            "--watch" => watch = true,
+            "--render" => render.format = RenderFormat::from_str(value()?).map_err(|_| "render must be ansi, none, ppm or svg")?,
+            "--render-to" => render.output = Some(PathBuf::from(value()?)),
+            "--timeout" => timeout = Some(parse_timeout(iter.next())?),
+            "--bag" | "--bag-file" => bag = Some(parse_bag(arg, value()?)?),
+            "--format" => json = match value()?.as_str() {
+                "text" => false,
+                "json" => true,
+                _ => return Err("format must be text or json".to_string()),
+            },
+            s if s.starts_with("--") => return Err(format!("unknown option `{s}`")),
+            _ => positional.push(arg),
+        }
+    }
+
+    // asking for a file implies wanting something in it
+    if render.output.is_some() && render.format == RenderFormat::None {
+        render.format = RenderFormat::Ansi;
+    }
+    // and stdout only has the JSON on it
+    if json && render.format != RenderFormat::None && render.output.is_none() {
+        return Err("can't render to the terminal with --format json, give a file with --render-to".to_string());
+    }
+
+    let (day, part) = positional.first().and_then(|s| parse_part(s, year)).ok_or("missing or invalid <day>:<part>")?;
+    let source = match positional.get(1) {
+        Some(s) => InputSource::from_str(s).map_err(|_| format!("invalid input `{s}`"))?,
+        None => InputSource::Local(InputKind::Real),
+    };
+    if watch && (json || matches!(source, InputSource::Stdin)) {
+        return Err("--watch needs a file to watch, and prints answers rather than JSON".to_string());
+    }
+    if bag.is_some() && (day.year, day.day) != (day2::PUZZLE.year, day2::PUZZLE.day) {
+        return Err("only day 2 of 2023 has a bag".to_string());
+    }
+
+    Ok(RunArgs { day, part, source, offline, render, timeout, json, watch, bag })
+}
+
+impl RunArgs {
+    // the part to run, with the settings from the command line (just day 2's bag so far) passed in rather than left
+    // for the part to find
+    fn solver(&self) -> impl Fn(String) -> SolveResult + Clone + Send + 'static {
+        let (solver, part, bag) = (self.day.part(self.part), self.part, self.bag.clone());
+        move |input| match &bag {
+            Some(bag) if part == 1 => day2::part1_with(input, bag),
+            Some(bag) => day2::part2_with(input, bag),
+            None => solver(input),
+        }
+    }
+}
+
+// runs a part with the time limit from the command line, if there is one, and says why if there's no solution
+fn solve(args: &RunArgs, input: String) -> Result<Solution, String> {
+    let Some(timeout) = args.timeout else {
+        return args.solver()(input).map_err(|e| e.to_string());
+    };
+    runner::run_with_timeout(args.solver(), input, timeout).map_err(|failure| match failure {
+        Failure::Parse(e) => e.to_string(),
+        failure => failure.to_string(),
+    })
+}
+
+// runs a part and prints its answer, or a JSON record of the run; returns whether it succeeded
+fn run_and_print(args: &RunArgs, input: String) -> bool {
+    let (day, part) = (args.day, args.part);
+    if !args.json {
+        return match solve(args, input) {
+            Ok(solution) => {
+                runner::print_solution(solution, &args.render);
+                true
+            }
+            Err(e) => {
+                eprintln!("error: {e}");
+                false
+            }
+        };
+    }
+
+    // crashes are part of the record rather than a backtrace
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let start = Instant::now();
+    let result = match args.timeout {
+        Some(timeout) => runner::run_with_timeout(args.solver(), input, timeout),
+        None => runner::run_catching(args.solver(), input),
+    };
+    panic::set_hook(hook);
+    let mut record = Record::new((day.year, day.day, part), args.source.to_string(), &result, start.elapsed());
+    record.peak_memory_bytes = record::peak_memory();
+    if let Ok(solution) = result {
+        if let Some(diagnostics) = solution.diagnostics {
+            log::info!("{diagnostics}");
+        }
+        if let Err(e) = render::show(&solution.frames, &args.render) {
+            log::warn!("could not render: {e}");
+        }
+    }
+    println!("{}", record.to_json());
+    record.succeeded()
+}
+
+// aoc_boilerplate only knows how to call `fn(String)`, so every entry points at this shim. It reads the part and
+// input kind aoc_boilerplate has just downloaded from the same command line (`<day>:<part> [real|example]`),
+// stores the input in the local cache so the next run doesn't need the network, and prints the solution
+fn run_downloaded(input: String) {
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let _ = take_log_level(&mut args);
+    let year = take_year(&mut args).ok().flatten().unwrap_or_else(registry::latest_year);
+    let args = parse_run_args(&args, year).expect("aoc_boilerplate ran a part without valid arguments");
+    let (day, part) = (args.day, args.part);
+    let kind = match args.source {
+        InputSource::Local(kind) => kind,
+        _ => InputKind::Real,
+    };
+
+    if let Err(e) = inputs::store_local(day.year, day.day, part, kind, &input) {
+        log::warn!("could not cache input: {e}");
+    }
+
+    if !run_and_print(&args, input) {
+        std::process::exit(1);
+    }
+}
+
+// watches the input the command line asked for and the example input, and re-runs the part when either changes
+fn watch_part(args: &RunArgs) -> ExitCode {
+    let (year, day, part) = (args.day.year, args.day.day, args.part);
+    let mut watched = vec![match &args.source {
+        InputSource::Local(kind) => (kind.to_string(), inputs::local_path(year, day, part, *kind)),
+        InputSource::File(path) => ("input".to_string(), path.clone()),
+        InputSource::Stdin => unreachable!("stdin can't be watched"),
+    }];
+    let example = inputs::local_path(year, day, part, InputKind::Example);
+    if watched.iter().all(|(_, path)| *path != example) {
+        watched.push((InputKind::Example.to_string(), example));
+    }
+    watch::watch(args.day, part, args.solver(), watched, args.timeout)
+}
+
+fn run_part(args: &[String], year: i32) -> ExitCode {
+    let args = match parse_run_args(args, year) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if args.watch {
+        return watch_part(&args);
+    }
+
+    match inputs::load(args.day.year, args.day.day, args.part, &args.source) {
+        Ok(input) => {
+            if run_and_print(&args, input) {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Err(InputError::NotCached(_)) if !args.offline => {
+            aoc_boilerplate::run(args.day.year, [[run_downloaded; 2]; 25]);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            let error = match e {
+                InputError::NotCached(_) => format!("{e}, and it can't be downloaded without AOC_SESSION (or with --offline)"),
+                e => e.to_string(),
+            };
+            if args.json {
+                let key = (args.day.year, args.day.day, args.part);
+                println!("{}", Record::no_input(key, args.source.to_string(), error).to_json());
+            } else {
+                eprintln!("{error}");
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    match take_log_level(&mut args) {
+        Ok(level) => StderrLogger::init(level.or_else(logging::level_from_env).unwrap_or(logging::DEFAULT_LEVEL)),
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    }
+    let explicit_year = match take_year(&mut args) {
+        Ok(year) => year,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let year = explicit_year.unwrap_or_else(registry::latest_year);
+
+    if args.first().is_some_and(|a| a == "lint") {
+        return run_lint(&args[1..], year);
+    }
+
+    if args.first().is_some_and(|a| a == "explain") {
+        return run_explain(&args[1..], year);
+    }
+
+    if args.first().is_some_and(|a| a == "query") {
+        return run_query(&args[1..], year);
+    }
+
+    if args.first().is_some_and(|a| a == "stream") {
+        return run_stream(&args[1..], year);
+    }
+
+    if args.first().is_some_and(|a| a == "submit") {
+        return run_submit(&args[1..], year);
+    }
+
+    if args.first().is_some_and(|a| a == "list") {
+        list(explicit_year);
+        return ExitCode::SUCCESS;
+    }
+
+    if args.first().is_some_and(|a| a == "verify") {
+        return run_verify(&args[1..], year);
+    }
+
+    if args.first().is_some_and(|a| a == "bench") {
+        let (parts, options) = match parse_bench_args(&args[1..], year) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                eprintln!("{e}");
+                eprintln!("usage: aoc23 bench <day>:<part>|all [--warmup N] [--runs N] [--input real|example] [--format table|json]");
+                return ExitCode::FAILURE;
+            }
+        };
+
+        return if bench::bench(&parts, &options) {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        };
+    }
+
+    if args.first().is_some_and(|a| a == "all") {
+        let options = match parse_all_args(&args[1..]) {
+            Ok(options) => options,
+            Err(e) => {
+                eprintln!("{e}");
+                eprintln!("usage: aoc23 all [real|example] [--jobs N] [--timeout <secs>] [--format table|json] [--diagnostics]");
+                return ExitCode::FAILURE;
+            }
+        };
+
+        return if all::run_all(&registry::year(year), &options) {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        };
+    }
+
+    run_part(&args, year)
 }
@@ -0,0 +1,177 @@
+//! Greatest common divisors, least common multiples and the Chinese Remainder Theorem, for the puzzles where several
+//! things repeat with different periods and the answer is when they line up.
+
+use std::ops::{Div, Rem};
+
+/// The unsigned integer types [`gcd`] and [`lcm`] work on.
+pub trait Unsigned: Copy + Eq + Div<Output = Self> + Rem<Output = Self> {
+    /// Zero.
+    const ZERO: Self;
+
+    /// Multiplies, or returns `None` on overflow.
+    fn checked_mul(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl Unsigned for $t {
+                const ZERO: Self = 0;
+
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, other)
+                }
+            }
+        )*
+    };
+}
+
+unsigned!(u32, u64, u128, usize);
+
+/// The greatest common divisor of `a` and `b` (`gcd(0, 0)` is 0).
+pub fn gcd<T: Unsigned>(mut a: T, mut b: T) -> T {
+    while b != T::ZERO {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// The least common multiple of `a` and `b`, or `None` if it doesn't fit in `T`. It's 0 if either is 0.
+pub fn checked_lcm<T: Unsigned>(a: T, b: T) -> Option<T> {
+    if a == T::ZERO || b == T::ZERO {
+        return Some(T::ZERO);
+    }
+    // divide first so only an lcm that really is too big overflows
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// The least common multiple of `a` and `b`.
+///
+/// # Panics
+///
+/// If it doesn't fit in `T`.
+pub fn lcm<T: Unsigned>(a: T, b: T) -> T {
+    checked_lcm(a, b).expect("lcm overflowed")
+}
+
+/// The extended Euclidean algorithm: `(g, x, y)` where `g` is the (non-negative) gcd of `a` and `b` and
+/// `a * x + b * y == g`.
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// Solves a system of congruences `x ≡ remainder (mod modulus)` with the Chinese Remainder Theorem. The moduli
+/// don't have to be coprime. Returns `(r, m)` such that the solutions are exactly the `x ≡ r (mod m)`, with
+/// `0 <= r < m`, or `None` if the congruences contradict each other or solving them overflows. Every modulus must
+/// be positive; an empty system gives `(0, 1)`.
+pub fn crt(congruences: impl IntoIterator<Item = (i128, i128)>) -> Option<(i128, i128)> {
+    congruences.into_iter().try_fold((0, 1), |(r1, m1), (r2, m2)| {
+        assert!(m2 > 0, "moduli must be positive");
+        let (g, p, _) = extended_gcd(m1, m2);
+        let difference = r2 - r1;
+        if difference % g != 0 {
+            return None;
+        }
+
+        // x = r1 + m1 * k, where m1 * k ≡ r2 - r1 (mod m2), so k ≡ (difference / g) * p (mod m2 / g)
+        let step = m2 / g;
+        let k = ((difference / g) % step).checked_mul(p % step)?.rem_euclid(step);
+        let m = m1.checked_mul(step)?;
+        Some((r1.checked_add(m1.checked_mul(k)?)?.rem_euclid(m), m))
+    })
+}
+
+/// Times that repeat forever: `offset`, `offset + period`, `offset + 2 * period` and so on.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Cycle {
+    /// The first time.
+    pub offset: u64,
+    /// The time between repeats; must be positive.
+    pub period: u64,
+}
+
+impl Cycle {
+    /// Whether `time` is one of the cycle's times.
+    pub fn contains(&self, time: u64) -> bool {
+        time >= self.offset && (time - self.offset) % self.period == 0
+    }
+}
+
+/// The earliest time that's in every one of `cycles`, or `None` if they never line up (or not before `u64::MAX`).
+pub fn first_common_time(cycles: &[Cycle]) -> Option<u64> {
+    let (r, m) = crt(cycles.iter().map(|c| ((c.offset % c.period) as i128, c.period as i128)))?;
+    // the solutions are r + k * m; the first one that counts is the first at or after every cycle's first time
+    let start = cycles.iter().map(|c| c.offset).max().unwrap_or(0) as i128;
+    let time = if r >= start {
+        r
+    } else {
+        r + (start - r + m - 1) / m * m
+    };
+    u64::try_from(time).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(gcd(12u64, 18), 6);
+        assert_eq!(gcd(0u64, 5), 5);
+        assert_eq!(gcd(0u64, 0), 0);
+        assert_eq!(lcm(4u64, 6), 12);
+        assert_eq!(lcm(0u64, 6), 0);
+        assert_eq!(lcm(2u128.pow(100), 3), 3 * 2u128.pow(100));
+    }
+
+    #[test]
+    fn lcm_overflow_is_caught() {
+        // a * b overflows here but the lcm doesn't
+        assert_eq!(checked_lcm(u64::MAX, u64::MAX), Some(u64::MAX));
+        assert_eq!(checked_lcm(u64::MAX, u64::MAX - 1), None);
+    }
+
+    #[test]
+    fn extended_gcd_gives_bezout_coefficients() {
+        for (a, b) in [(240, 46), (46, 240), (-12, 18), (7, 0), (0, 0), (17, 5)] {
+            let (g, x, y) = extended_gcd(a, b);
+            assert_eq!(g, gcd(a.unsigned_abs(), b.unsigned_abs()) as i128);
+            assert_eq!(a * x + b * y, g);
+        }
+    }
+
+    #[test]
+    fn crt_with_and_without_coprime_moduli() {
+        assert_eq!(crt([(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt([(1, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt([(1, 4), (2, 6)]), None);
+        assert_eq!(crt([(-1, 5), (0, 5)]), None);
+        assert_eq!(crt([(-1, 5), (4, 5)]), Some((4, 5)));
+        assert_eq!(crt([]), Some((0, 1)));
+        assert_eq!(crt([(0, i128::MAX), (1, i128::MAX - 1)]), None);
+    }
+
+    #[test]
+    fn cycles_line_up_after_their_offsets() {
+        let cycle = |offset, period| Cycle { offset, period };
+        assert_eq!(first_common_time(&[cycle(3, 3), cycle(5, 5)]), Some(15));
+        assert_eq!(first_common_time(&[cycle(7, 2), cycle(2, 4)]), None);
+        // 1 mod 4 and 2 mod 3 first meet at 5, but the second cycle doesn't start until 11
+        assert_eq!(first_common_time(&[cycle(1, 4), cycle(11, 3)]), Some(17));
+        assert!(cycle(11, 3).contains(17) && !cycle(11, 3).contains(5));
+        assert_eq!(first_common_time(&[cycle(u64::MAX - 1, u64::MAX), cycle(0, u64::MAX - 1)]), None);
+    }
+}
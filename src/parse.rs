@@ -0,0 +1,183 @@
+//! Helpers for parsing puzzle input that report where in the input something went wrong.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Malformed puzzle input, with the position and text that couldn't be parsed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    /// The day whose input this is.
+    pub day: usize,
+    /// 1-based; 0 means the error isn't tied to a particular line/column (e.g. the input is empty).
+    pub line: usize,
+    /// 1-based, in characters.
+    pub column: usize,
+    /// The offending text, if any.
+    pub text: String,
+    /// What was wrong with it.
+    pub message: String,
+}
+
+impl ParseError {
+    /// An error at a particular line and column.
+    pub fn new(day: usize, line: usize, column: usize, text: &str, message: impl Into<String>) -> Self {
+        ParseError {
+            day,
+            line,
+            column,
+            text: text.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// An error about the input as a whole rather than any particular line.
+    pub fn whole_input(day: usize, message: impl Into<String>) -> Self {
+        Self::new(day, 0, 0, "", message)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "day {}", self.day)?;
+        if self.line > 0 {
+            write!(f, ", line {}, column {}", self.line, self.column)?;
+        }
+        write!(f, ": {}", self.message)?;
+        if !self.text.is_empty() {
+            write!(f, " (found `{}`)", self.text.escape_debug())?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseError {}
+
+/// A line of puzzle input that knows where it came from, so anything parsed out of it can report its position.
+#[derive(Copy, Clone, Debug)]
+pub struct Line<'a> {
+    /// The day whose input this is.
+    pub day: usize,
+    /// 1-based line number.
+    pub number: usize,
+    /// The line itself, without the line ending.
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    /// An error about `part`, which should be a slice of `self.text` (e.g. from `split`); the column is worked out
+    /// from where it sits.
+    pub fn error(&self, part: &str, message: impl Into<String>) -> ParseError {
+        let offset = (part.as_ptr() as usize).wrapping_sub(self.text.as_ptr() as usize);
+        let column = if offset <= self.text.len() {
+            self.text[..offset].chars().count() + 1
+        } else {
+            1
+        };
+        ParseError::new(self.day, self.number, column, part, message)
+    }
+
+    /// An error at a 0-based character column.
+    pub fn error_at(&self, column: usize, text: &str, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.day, self.number, column + 1, text, message)
+    }
+
+    /// Parses `part` (trimmed), describing it as `what` if it's invalid.
+    pub fn parse<T: FromStr>(&self, part: &'a str, what: &str) -> Result<T, ParseError> {
+        T::from_str(part.trim()).map_err(|_| self.error(part.trim(), format!("invalid {what}")))
+    }
+
+    /// Splits `part` at the first `delimiter`, which must be there.
+    pub fn split_once(&self, part: &'a str, delimiter: &str) -> Result<(&'a str, &'a str), ParseError> {
+        part.split_once(delimiter).ok_or_else(|| self.error(part, format!("expected `{delimiter}`")))
+    }
+
+    /// Strips `prefix` from `part`, which must start with it.
+    pub fn strip_prefix(&self, part: &'a str, prefix: &str) -> Result<&'a str, ParseError> {
+        part.strip_prefix(prefix).ok_or_else(|| self.error(part, format!("expected `{prefix}`")))
+    }
+}
+
+/// The lines of `input`, numbered from 1.
+pub fn lines(day: usize, input: &str) -> impl Iterator<Item = Line<'_>> {
+    input.lines().enumerate().map(move |(n, text)| Line { day, number: n + 1, text })
+}
+
+/// Splits the input into blank-line separated sections, keeping the original line numbers.
+pub fn sections(day: usize, input: &str) -> Vec<Vec<Line<'_>>> {
+    let mut sections = vec![vec![]];
+    for line in lines(day, input) {
+        if line.text.trim().is_empty() {
+            if !sections.last().unwrap().is_empty() {
+                sections.push(vec![]);
+            }
+        } else {
+            sections.last_mut().unwrap().push(line);
+        }
+    }
+    if sections.last().unwrap().is_empty() {
+        sections.pop();
+    }
+    sections
+}
+
+/// Parses a rectangular character map, mapping each character through `f` (which returns `None` for characters
+/// that aren't allowed).
+pub fn char_grid<T>(lines: &[Line], mut f: impl FnMut(char) -> Option<T>) -> Result<Vec<Vec<T>>, ParseError> {
+    let mut width = None;
+    lines.iter().map(|line| {
+        let row = line.text.char_indices().enumerate().map(|(x, (i, c))| {
+            f(c).ok_or_else(|| line.error_at(x, &line.text[i..i + c.len_utf8()], "unexpected character"))
+        }).collect::<Result<Vec<_>, _>>()?;
+
+        match width {
+            None => width = Some(row.len()),
+            Some(w) if w != row.len() => return Err(line.error_at(
+                row.len().min(w),
+                "",
+                format!("row is {} characters wide, but the first row is {w}", row.len()),
+            )),
+            _ => {}
+        }
+
+        Ok(row)
+    }).collect()
+}
+
+/// Parses the whole input as a character map (see [`char_grid`]).
+pub fn input_grid<T>(day: usize, input: &str, f: impl FnMut(char) -> Option<T>) -> Result<Vec<Vec<T>>, ParseError> {
+    let lines = lines(day, non_empty(day, input)?).collect::<Vec<_>>();
+    char_grid(&lines, f)
+}
+
+/// The error from a parser that stops at the first one, as the list of errors an input checker returns.
+pub fn errors<T>(result: Result<T, ParseError>) -> Vec<ParseError> {
+    result.err().into_iter().collect()
+}
+
+/// Every error `check` finds in an input whose lines (or grid rows) can be checked on their own: it runs on each
+/// non-blank line by itself, and then, if none of them had an error, on the whole input for anything that spans
+/// lines. An error about a line on its own that isn't tied to a position in it (like a map without its start) is
+/// left for the whole input.
+pub fn line_errors<T>(input: &str, check: impl Fn(&str) -> Result<T, ParseError>) -> Vec<ParseError> {
+    let errors = input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(n, line)| check(line).err().filter(|e| e.line > 0).map(|e| ParseError { line: n + 1, ..e }))
+        .collect::<Vec<_>>();
+    if errors.is_empty() {
+        self::errors(check(input))
+    } else {
+        errors
+    }
+}
+
+/// Checks that the input isn't empty (or all whitespace).
+pub fn non_empty<'a>(day: usize, input: &'a str) -> Result<&'a str, ParseError> {
+    if input.trim().is_empty() {
+        Err(ParseError::whole_input(day, "input is empty"))
+    } else {
+        Ok(input)
+    }
+}
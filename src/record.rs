@@ -0,0 +1,135 @@
+//! Machine-readable records of running parts, for dashboards and scripts.
+//!
+//! With `--format json` the runner prints one [`Record`] per part as a line of JSON, in place of the answer (or the
+//! answer table), and nothing else goes to stdout.
+
+use std::fs;
+use std::time::Duration;
+use serde::Serialize;
+use crate::runner::Failure;
+use crate::solution::{Answer, Solution};
+
+/// How running a part ended.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// It produced an answer.
+    Ok,
+    /// Its input couldn't be read or parsed, or it panicked.
+    Error,
+    /// It ran out of time.
+    Timeout,
+    /// It has no answer to give (like day 25 part 2).
+    Unimplemented,
+}
+
+/// One part's run.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Record {
+    /// The year of the puzzle.
+    pub year: i32,
+    /// The day of the puzzle.
+    pub day: usize,
+    /// The part that was run.
+    pub part: usize,
+    /// Where the input came from: `real`, `example`, a file path or `stdin`.
+    pub input: String,
+    /// How it ended.
+    pub status: Status,
+    /// The answer, if there is one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+    /// What went wrong, or why there's no answer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// How long the part ran for, in nanoseconds.
+    pub elapsed_ns: u64,
+    /// The most memory the process had used by the time the part finished, in bytes, where that's known.
+    pub peak_memory_bytes: Option<u64>,
+}
+
+impl Record {
+    /// A record of a part that ran (successfully or not) for `elapsed`.
+    pub fn new(
+        (year, day, part): (i32, usize, usize),
+        input: String,
+        result: &Result<Solution, Failure>,
+        elapsed: Duration,
+    ) -> Self {
+        let (status, answer, error) = match result {
+            Ok(Solution { answer: Answer::Unimplemented(reason), .. }) => (Status::Unimplemented, None, Some(reason.clone())),
+            Ok(solution) => (Status::Ok, Some(solution.answer.to_string()), None),
+            Err(failure @ Failure::TimedOut(_)) => (Status::Timeout, None, Some(failure.to_string())),
+            Err(failure) => (Status::Error, None, Some(failure.to_string())),
+        };
+        Record {
+            year,
+            day,
+            part,
+            input,
+            status,
+            answer,
+            error,
+            elapsed_ns: elapsed.as_nanos() as u64,
+            peak_memory_bytes: None,
+        }
+    }
+
+    /// A record of a part that couldn't be run because its input couldn't be read.
+    pub fn no_input((year, day, part): (i32, usize, usize), input: String, error: String) -> Self {
+        Record {
+            year,
+            day,
+            part,
+            input,
+            status: Status::Error,
+            answer: None,
+            error: Some(error),
+            elapsed_ns: 0,
+            peak_memory_bytes: None,
+        }
+    }
+
+    /// Whether the part gave an answer, or had none to give.
+    pub fn succeeded(&self) -> bool {
+        matches!(self.status, Status::Ok | Status::Unimplemented)
+    }
+
+    /// The record as a single line of JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("records always serialize")
+    }
+}
+
+/// The process's peak resident memory so far, in bytes, if the OS reports it (Linux does, in `/proc`).
+pub fn peak_memory() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let kb = status.lines().find_map(|line| line.strip_prefix("VmHWM:"))?.trim().strip_suffix("kB")?;
+    kb.trim().parse::<u64>().ok().map(|kb| kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statuses() {
+        let record = |result| Record::new((2023, 1, 2), "example".to_string(), &result, Duration::from_micros(3));
+        let ok = record(Ok(Solution::new(281)));
+        assert_eq!(
+            ok.to_json(),
+            r#"{"year":2023,"day":1,"part":2,"input":"example","status":"ok","answer":"281","elapsed_ns":3000,"peak_memory_bytes":null}"#,
+        );
+        assert!(ok.succeeded());
+
+        let unimplemented = record(Ok(Solution::unimplemented("no part 2")));
+        assert_eq!((unimplemented.status, unimplemented.error.as_deref()), (Status::Unimplemented, Some("no part 2")));
+        assert!(unimplemented.succeeded());
+
+        let timeout = record(Err(Failure::TimedOut(None)));
+        assert_eq!((timeout.status, timeout.error.as_deref()), (Status::Timeout, Some("timed out")));
+        assert!(!timeout.succeeded());
+
+        assert_eq!(record(Err(Failure::Panic("oops".to_string()))).status, Status::Error);
+    }
+}
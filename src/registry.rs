@@ -0,0 +1,122 @@
+//! The puzzles the binary knows about.
+//!
+//! Each day module registers itself once with [`puzzle!`], which records its year, day number, title, parts and
+//! input checker as a `PUZZLE` constant. A year's `mod.rs` lists its day modules with [`calendar!`], and
+//! [`CALENDARS`] collects every year, so the runner can find any day without a hand-maintained table.
+
+use crate::parse::ParseError;
+use crate::solution::Solver;
+
+/// One registered puzzle.
+#[derive(Copy, Clone, Debug)]
+pub struct Day {
+    /// The year of the event.
+    pub year: i32,
+    /// The day of the month, from 1.
+    pub day: usize,
+    /// The puzzle's title.
+    pub title: &'static str,
+    /// Part 1 and part 2.
+    pub parts: [Solver; 2],
+    /// Runs the day's parser over an input without solving it (which is what `aoc23 lint` does), returning every
+    /// error it finds.
+    pub check_input: fn(&str) -> Vec<ParseError>,
+}
+
+impl Day {
+    /// Part `part` (1 or 2).
+    pub fn part(&self, part: usize) -> Solver {
+        self.parts[part - 1]
+    }
+}
+
+/// Registers the module it's used in as a day: `puzzle!(<year>, <day>, "<title>", part1, part2, <check>)`, where
+/// `check` parses an input (a `&str`) without solving it and returns the errors it finds, usually with
+/// [`parse::line_errors`](crate::parse::line_errors) or [`parse::errors`](crate::parse::errors). It defines the
+/// module's `DAY` (the day number its parse errors are reported against) and its public `PUZZLE`.
+macro_rules! puzzle {
+    ($year:literal, $day:literal, $title:literal, $part1:path, $part2:path, $check:expr) => {
+        #[allow(dead_code)]
+        const DAY: usize = $day;
+
+        #[doc = concat!("Day ", $day, " of ", $year, ": ", $title)]
+        pub const PUZZLE: $crate::registry::Day = $crate::registry::Day {
+            year: $year,
+            day: $day,
+            title: $title,
+            parts: [$part1, $part2],
+            check_input: $check,
+        };
+    };
+}
+
+/// Declares a year's day modules and lists their `PUZZLE`s, in order, as that year's `PUZZLES`.
+macro_rules! calendar {
+    ($($module:ident),* $(,)?) => {
+        $(
+            #[doc = concat!("The solutions to `", stringify!($module), "`; see its `PUZZLE` for the title.")]
+            #[rustfmt::skip]
+            pub mod $module;
+        )*
+
+        /// Every day of the year, in order.
+        pub static PUZZLES: &[$crate::registry::Day] = &[$($module::PUZZLE),*];
+    };
+}
+
+pub(crate) use {calendar, puzzle};
+
+/// The days of every year, one list per year. A new year's module adds its `PUZZLES` here.
+pub static CALENDARS: &[&[Day]] = &[crate::days::PUZZLES];
+
+/// Every registered day, by year and then day.
+pub fn all() -> Vec<&'static Day> {
+    let mut days = CALENDARS.iter().flat_map(|calendar| calendar.iter()).collect::<Vec<_>>();
+    days.sort_by_key(|d| (d.year, d.day));
+    days
+}
+
+/// The registered days of `year`, in order.
+pub fn year(year: i32) -> Vec<&'static Day> {
+    all().into_iter().filter(|d| d.year == year).collect()
+}
+
+/// Every year with registered days, in order.
+pub fn years() -> Vec<i32> {
+    let mut years = all().iter().map(|d| d.year).collect::<Vec<_>>();
+    years.dedup();
+    years
+}
+
+/// The most recent year, which the runner uses unless it's told otherwise.
+pub fn latest_year() -> i32 {
+    years().last().copied().expect("no days are registered")
+}
+
+/// Day `day` of `year`, if it's registered.
+pub fn find(year: i32, day: usize) -> Option<&'static Day> {
+    CALENDARS.iter().flat_map(|calendar| calendar.iter()).find(|d| d.year == year && d.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use super::*;
+
+    #[test]
+    fn every_day_is_registered_once() {
+        let days = all();
+        let unique = days.iter().map(|d| (d.year, d.day)).collect::<HashSet<_>>();
+        assert_eq!(unique.len(), days.len());
+        assert_eq!(year(2023).iter().map(|d| d.day).collect::<Vec<_>>(), (1..=25).collect::<Vec<_>>());
+        assert!(days.iter().all(|d| !d.title.is_empty()));
+    }
+
+    #[test]
+    fn lookup() {
+        assert_eq!(find(2023, 1).map(|d| d.title), Some("Trebuchet?!"));
+        assert!(find(2023, 26).is_none());
+        assert!(find(1999, 1).is_none());
+        assert_eq!(latest_year(), 2023);
+    }
+}
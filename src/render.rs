@@ -0,0 +1,290 @@
+//! Drawing the pictures some days make of their puzzles, to the terminal or to an image file.
+//!
+//! Solvers don't draw anything themselves: they attach [`Frame`]s to their [`Solution`](crate::solution::Solution)
+//! and the caller decides whether and how to show them.
+
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use colored::Colorize;
+
+/// A 24-bit colour.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    /// White.
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+    /// The colour of a glyph that doesn't have one of its own in an image.
+    pub const DEFAULT: Rgb = Rgb(192, 192, 192);
+    /// The background of an image.
+    pub const BACKGROUND: Rgb = Rgb(0, 0, 0);
+}
+
+/// One character cell of a frame.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Cell {
+    /// What to draw; a space is left blank.
+    pub glyph: char,
+    /// The colour to draw it in, or `None` for the terminal's (or image's) default.
+    pub colour: Option<Rgb>,
+}
+
+impl Cell {
+    /// An empty cell.
+    pub const BLANK: Cell = Cell { glyph: ' ', colour: None };
+}
+
+/// A grid of coloured characters, e.g. a puzzle map with the solution drawn on it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+}
+
+impl Frame {
+    /// A blank frame.
+    pub fn new(width: usize, height: usize) -> Self {
+        Frame {
+            width,
+            height,
+            cells: vec![Cell::BLANK; width * height],
+        }
+    }
+
+    /// The width in cells.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height in cells.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The cell at `(x, y)`.
+    pub fn get(&self, x: usize, y: usize) -> Cell {
+        self.cells[y * self.width + x]
+    }
+
+    /// Draws `glyph` at `(x, y)`.
+    pub fn set(&mut self, x: usize, y: usize, glyph: char, colour: Option<Rgb>) {
+        self.cells[y * self.width + x] = Cell { glyph, colour };
+    }
+
+    fn rows(&self) -> impl Iterator<Item = &[Cell]> {
+        self.cells.chunks(self.width.max(1)).take(self.height)
+    }
+}
+
+/// How to show frames.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RenderFormat {
+    /// Coloured text with ANSI escape codes.
+    Ansi,
+    /// Don't show them at all.
+    None,
+    /// A binary PPM image, with each cell as a square of pixels.
+    Ppm,
+    /// An SVG image of the text.
+    Svg,
+}
+
+impl RenderFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            RenderFormat::Ansi => "txt",
+            RenderFormat::None => "",
+            RenderFormat::Ppm => "ppm",
+            RenderFormat::Svg => "svg",
+        }
+    }
+}
+
+impl Display for RenderFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            RenderFormat::Ansi => "ansi",
+            RenderFormat::None => "none",
+            RenderFormat::Ppm => "ppm",
+            RenderFormat::Svg => "svg",
+        })
+    }
+}
+
+impl FromStr for RenderFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ansi" => Ok(RenderFormat::Ansi),
+            "none" => Ok(RenderFormat::None),
+            "ppm" => Ok(RenderFormat::Ppm),
+            "svg" => Ok(RenderFormat::Svg),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Where and how to show frames.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RenderOptions {
+    /// The format to draw them in.
+    pub format: RenderFormat,
+    /// The file to write them to; the terminal if `None`.
+    pub output: Option<PathBuf>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            format: RenderFormat::None,
+            output: None,
+        }
+    }
+}
+
+// the size in pixels of one cell of a PPM image, and the size in pixels of a character in an SVG
+const PPM_CELL_SIZE: usize = 4;
+const SVG_CELL_WIDTH: usize = 10;
+const SVG_CELL_HEIGHT: usize = 18;
+
+fn write_ansi(frame: &Frame, w: &mut impl Write) -> io::Result<()> {
+    for row in frame.rows() {
+        for cell in row {
+            match cell.colour {
+                Some(Rgb(r, g, b)) => write!(w, "{}", cell.glyph.to_string().truecolor(r, g, b))?,
+                None => write!(w, "{}", cell.glyph)?,
+            }
+        }
+        writeln!(w)?;
+    }
+    Ok(())
+}
+
+fn write_ppm(frame: &Frame, w: &mut impl Write) -> io::Result<()> {
+    write!(w, "P6\n{} {}\n255\n", frame.width * PPM_CELL_SIZE, frame.height * PPM_CELL_SIZE)?;
+    for row in frame.rows() {
+        let pixels = row.iter().flat_map(|cell| {
+            let Rgb(r, g, b) = match cell.glyph {
+                ' ' => Rgb::BACKGROUND,
+                _ => cell.colour.unwrap_or(Rgb::DEFAULT),
+            };
+            [r, g, b].repeat(PPM_CELL_SIZE)
+        }).collect::<Vec<u8>>();
+        for _ in 0..PPM_CELL_SIZE {
+            w.write_all(&pixels)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_svg(frame: &Frame, w: &mut impl Write) -> io::Result<()> {
+    let (width, height) = (frame.width * SVG_CELL_WIDTH, frame.height * SVG_CELL_HEIGHT);
+    writeln!(w, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" font-family="monospace" font-size="16">"#)?;
+    let Rgb(r, g, b) = Rgb::BACKGROUND;
+    writeln!(w, r#"<rect width="100%" height="100%" fill="rgb({r},{g},{b})"/>"#)?;
+    for (y, row) in frame.rows().enumerate() {
+        for (x, cell) in row.iter().enumerate().filter(|(_, c)| c.glyph != ' ') {
+            let Rgb(r, g, b) = cell.colour.unwrap_or(Rgb::DEFAULT);
+            let glyph = match cell.glyph {
+                '&' => "&amp;".to_string(),
+                '<' => "&lt;".to_string(),
+                '>' => "&gt;".to_string(),
+                c => c.to_string(),
+            };
+            writeln!(
+                w,
+                r#"<text x="{}" y="{}" fill="rgb({r},{g},{b})">{glyph}</text>"#,
+                x * SVG_CELL_WIDTH,
+                (y + 1) * SVG_CELL_HEIGHT - SVG_CELL_HEIGHT / 4,
+            )?;
+        }
+    }
+    writeln!(w, "</svg>")
+}
+
+/// Draws a frame in the given format.
+pub fn write_frame(frame: &Frame, format: RenderFormat, w: &mut impl Write) -> io::Result<()> {
+    match format {
+        RenderFormat::Ansi => write_ansi(frame, w),
+        RenderFormat::None => Ok(()),
+        RenderFormat::Ppm => write_ppm(frame, w),
+        RenderFormat::Svg => write_svg(frame, w),
+    }
+}
+
+// with more than one frame, each gets its own numbered file next to `path`
+fn frame_path(path: &Path, format: RenderFormat, index: usize, count: usize) -> PathBuf {
+    if count == 1 {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let extension = path.extension().map(|s| s.to_string_lossy().to_string());
+    path.with_file_name(format!("{stem}-{}.{}", index + 1, extension.as_deref().unwrap_or(format.extension())))
+}
+
+/// Shows frames as `options` says. Images can't go to the terminal, so they need an output file.
+pub fn show(frames: &[Frame], options: &RenderOptions) -> io::Result<()> {
+    if options.format == RenderFormat::None || frames.is_empty() {
+        return Ok(());
+    }
+
+    match &options.output {
+        None if options.format == RenderFormat::Ansi => {
+            let mut stdout = io::stdout().lock();
+            frames.iter().try_for_each(|frame| write_frame(frame, options.format, &mut stdout))
+        }
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} frames can't be shown in the terminal, give a file to write them to", options.format),
+        )),
+        Some(path) => frames.iter().enumerate().try_for_each(|(i, frame)| {
+            let mut w = BufWriter::new(File::create(frame_path(path, options.format, i, frames.len()))?);
+            write_frame(frame, options.format, &mut w)?;
+            w.flush()
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(frame: &Frame, format: RenderFormat) -> Vec<u8> {
+        let mut out = vec![];
+        write_frame(frame, format, &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn ppm_is_sized_by_cells() {
+        let mut frame = Frame::new(3, 2);
+        frame.set(1, 0, '#', Some(Rgb(1, 2, 3)));
+        let ppm = render(&frame, RenderFormat::Ppm);
+        let header = format!("P6\n{} {}\n255\n", 3 * PPM_CELL_SIZE, 2 * PPM_CELL_SIZE);
+        assert!(ppm.starts_with(header.as_bytes()));
+        assert_eq!(ppm.len(), header.len() + 3 * 2 * PPM_CELL_SIZE * PPM_CELL_SIZE * 3);
+        assert_eq!(&ppm[header.len() + PPM_CELL_SIZE * 3..][..3], &[1, 2, 3]);
+    }
+
+    #[test]
+    fn svg_escapes_glyphs() {
+        let mut frame = Frame::new(2, 1);
+        frame.set(0, 0, '<', None);
+        let svg = String::from_utf8(render(&frame, RenderFormat::Svg)).unwrap();
+        assert!(svg.contains(">&lt;</text>"));
+        assert_eq!(svg.matches("<text").count(), 1);
+    }
+
+    #[test]
+    fn several_frames_get_numbered_files() {
+        let path = Path::new("out/frame.svg");
+        assert_eq!(frame_path(path, RenderFormat::Svg, 0, 1), PathBuf::from("out/frame.svg"));
+        assert_eq!(frame_path(path, RenderFormat::Svg, 1, 3), PathBuf::from("out/frame-2.svg"));
+        assert_eq!(frame_path(Path::new("frame"), RenderFormat::Ppm, 0, 2), PathBuf::from("frame-1.ppm"));
+    }
+}
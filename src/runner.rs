@@ -0,0 +1,127 @@
+//! Running solvers safely.
+
+use std::any::Any;
+use std::fmt::{Display, Formatter};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+use crate::cancel::{self, CancellationToken, Cancelled};
+use crate::parse::ParseError;
+use crate::render::{self, RenderOptions};
+use crate::solution::{Solution, SolveResult};
+
+/// Why a part didn't produce a solution.
+#[derive(Debug)]
+pub enum Failure {
+    /// The input couldn't be parsed.
+    Parse(ParseError),
+    /// The solver panicked, with the panic message.
+    Panic(String),
+    /// The solver ran out of time, with how far it got if it said.
+    TimedOut(Option<String>),
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Failure::Parse(e) => write!(f, "invalid input: {e}"),
+            Failure::Panic(message) => write!(f, "panicked: {message}"),
+            Failure::TimedOut(None) => write!(f, "timed out"),
+            Failure::TimedOut(Some(progress)) => write!(f, "timed out ({progress})"),
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "solver panicked".to_string()
+    }
+}
+
+/// Runs a solver (a [`Solver`](crate::solution::Solver), or a closure that calls one with extra settings), turning
+/// a panic into an error so one broken part can't take down a whole batch.
+pub fn run_catching(solver: impl FnOnce(String) -> SolveResult, input: String) -> Result<Solution, Failure> {
+    match panic::catch_unwind(AssertUnwindSafe(|| solver(input))) {
+        Ok(Ok(solution)) => Ok(solution),
+        Ok(Err(e)) => Err(Failure::Parse(e)),
+        Err(payload) => match payload.downcast::<Cancelled>() {
+            Ok(cancelled) => Err(Failure::TimedOut(cancelled.progress)),
+            Err(payload) => Err(Failure::Panic(panic_message(payload))),
+        },
+    }
+}
+
+// how long a cancelled solver gets to reach a checkpoint before it's given up on
+const GRACE_PERIOD: Duration = Duration::from_secs(1);
+
+/// Runs a solver like [`run_catching`], but cancels it if it takes longer than `timeout`. The solver runs on its own
+/// thread; one that doesn't reach a [checkpoint](cancel::checkpoint) soon after being cancelled is left to finish
+/// in the background.
+pub fn run_with_timeout(
+    solver: impl FnOnce(String) -> SolveResult + Send + 'static,
+    input: String,
+    timeout: Duration,
+) -> Result<Solution, Failure> {
+    let token = CancellationToken::new();
+    let (tx, rx) = mpsc::channel();
+    let solver_token = token.clone();
+    thread::spawn(move || {
+        let result = cancel::with_token(solver_token, || run_catching(solver, input));
+        // nobody's listening any more if the solver was given up on
+        let _ = tx.send(result);
+    });
+
+    match rx.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => {
+            token.cancel();
+            rx.recv_timeout(GRACE_PERIOD).unwrap_or(Err(Failure::TimedOut(None)))
+        }
+        Err(RecvTimeoutError::Disconnected) => Err(Failure::Panic("solver thread exited".to_string())),
+    }
+}
+
+/// Logs a solution's diagnostics (if any) and shows its frames, then prints its answer, which is the only thing that
+/// goes to stdout.
+pub fn print_solution(solution: Solution, render: &RenderOptions) {
+    if let Some(diagnostics) = solution.diagnostics {
+        log::info!("{diagnostics}");
+    }
+    if let Err(e) = render::show(&solution.frames, render) {
+        log::warn!("could not render: {e}");
+    }
+    println!("{}", solution.answer);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::SolveResult;
+
+    fn forever(_: String) -> SolveResult {
+        for i in 0.. {
+            cancel::checkpoint_with(|| format!("{i} iterations"));
+            thread::sleep(Duration::from_millis(1));
+        }
+        unreachable!()
+    }
+
+    fn quick(input: String) -> SolveResult {
+        Ok(Solution::new(input.len()))
+    }
+
+    #[test]
+    fn slow_solvers_are_stopped_with_their_progress() {
+        match run_with_timeout(forever, String::new(), Duration::from_millis(50)) {
+            Err(Failure::TimedOut(Some(progress))) => assert!(progress.ends_with(" iterations"), "{progress}"),
+            other => panic!("expected a timeout, got {other:?}"),
+        }
+        let solution = run_with_timeout(quick, "abc".to_string(), Duration::from_secs(10)).unwrap();
+        assert_eq!(solution.answer, 3.into());
+    }
+}
@@ -0,0 +1,103 @@
+//! What a part returns when it's run.
+
+use std::fmt::{Display, Formatter};
+use crate::parse::ParseError;
+use crate::render::Frame;
+
+/// The answer to one part of a puzzle.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Answer {
+    /// A numeric answer (almost all of them).
+    Int(i64),
+    /// A textual answer.
+    Str(String),
+    /// The part has no solution, with the reason why.
+    Unimplemented(String),
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{n}"),
+            Answer::Str(s) => write!(f, "{s}"),
+            Answer::Unimplemented(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+macro_rules! answer_from_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    Answer::Int(i64::try_from(n).expect("answer does not fit in an i64"))
+                }
+            }
+        )*
+    };
+}
+
+answer_from_int!(i32, i64, isize, u8, u32, u64, usize);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Str(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Str(s.to_string())
+    }
+}
+
+/// The result of solving a part: its answer, plus anything else the solver wants to show alongside it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Solution {
+    /// The answer to submit.
+    pub answer: Answer,
+    /// Extra text output, logged at `info` level before the answer is printed.
+    pub diagnostics: Option<String>,
+    /// Pictures of the puzzle, shown if rendering is turned on.
+    pub frames: Vec<Frame>,
+}
+
+impl Solution {
+    /// A solution with just an answer.
+    pub fn new(answer: impl Into<Answer>) -> Self {
+        Solution {
+            answer: answer.into(),
+            diagnostics: None,
+            frames: vec![],
+        }
+    }
+
+    /// A solution for a part that has no answer.
+    pub fn unimplemented(reason: &str) -> Self {
+        Solution {
+            answer: Answer::Unimplemented(reason.to_string()),
+            diagnostics: None,
+            frames: vec![],
+        }
+    }
+
+    /// Attaches extra output to the solution.
+    pub fn with_diagnostics(self, diagnostics: String) -> Self {
+        Solution {
+            diagnostics: Some(diagnostics),
+            ..self
+        }
+    }
+
+    /// Attaches a picture of the puzzle to the solution.
+    pub fn with_frame(mut self, frame: Frame) -> Self {
+        self.frames.push(frame);
+        self
+    }
+}
+
+/// What every part returns; bad input is reported as a [`ParseError`] rather than a panic.
+pub type SolveResult = Result<Solution, ParseError>;
+
+/// The signature shared by every `part1` and `part2`.
+pub type Solver = fn(String) -> SolveResult;
@@ -0,0 +1,384 @@
+//! Submitting answers to the Advent of Code website.
+//!
+//! Every verdict the site gives is remembered in a guesses file, so the same wrong answer is never sent twice, and an
+//! answer that's on the wrong side of an earlier "too high" or "too low" isn't sent at all.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// The Advent of Code website.
+pub const DEFAULT_SERVER: &str = "https://adventofcode.com";
+
+/// The default guesses file, relative to the working directory.
+pub const GUESSES_FILE: &str = "guesses.txt";
+
+/// What the site said about an answer.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Verdict {
+    /// The answer is right.
+    Correct,
+    /// The answer is wrong, and the site didn't say which way.
+    Incorrect,
+    /// The answer is wrong, and too high.
+    TooHigh,
+    /// The answer is wrong, and too low.
+    TooLow,
+    /// An answer was sent too recently; try again after the wait, if the site said how long.
+    RateLimited(Option<Duration>),
+    /// The part is already solved, or part 1 isn't yet.
+    WrongLevel,
+    /// A response the parser didn't recognise, with its text.
+    Unknown(String),
+}
+
+impl Verdict {
+    /// Whether the verdict says anything about the answer itself (rather than when or whether it could be checked).
+    pub fn is_about_answer(&self) -> bool {
+        matches!(self, Verdict::Correct | Verdict::Incorrect | Verdict::TooHigh | Verdict::TooLow)
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Correct => write!(f, "correct"),
+            Verdict::Incorrect => write!(f, "incorrect"),
+            Verdict::TooHigh => write!(f, "too-high"),
+            Verdict::TooLow => write!(f, "too-low"),
+            Verdict::RateLimited(None) => write!(f, "rate-limited"),
+            Verdict::RateLimited(Some(wait)) => write!(f, "rate-limited (wait {}s)", wait.as_secs()),
+            Verdict::WrongLevel => write!(f, "wrong-level"),
+            Verdict::Unknown(text) => write!(f, "unknown response: {text}"),
+        }
+    }
+}
+
+impl FromStr for Verdict {
+    type Err = ();
+
+    // only the verdicts that are about the answer are stored
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "correct" => Ok(Verdict::Correct),
+            "incorrect" => Ok(Verdict::Incorrect),
+            "too-high" => Ok(Verdict::TooHigh),
+            "too-low" => Ok(Verdict::TooLow),
+            _ => Err(()),
+        }
+    }
+}
+
+// the text of the page's `<article>`, which is where the site puts its verdict, without the markup
+fn article_text(html: &str) -> String {
+    let article = html
+        .split_once("<article")
+        .and_then(|(_, rest)| rest.split_once('>'))
+        .map_or(html, |(_, rest)| rest.split_once("</article>").map_or(rest, |(article, _)| article));
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in article.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// `You have 1m 5s left to wait.`
+fn wait_time(text: &str) -> Option<Duration> {
+    let (_, rest) = text.split_once("You have ")?;
+    let (wait, _) = rest.split_once(" left to wait")?;
+    wait.split_whitespace().try_fold(Duration::ZERO, |total, part| {
+        let (n, unit) = part.split_at(part.find(|c: char| !c.is_ascii_digit())?);
+        let n = u64::from_str(n).ok()?;
+        Some(total + Duration::from_secs(match unit {
+            "h" => n * 3600,
+            "m" => n * 60,
+            "s" => n,
+            _ => return None,
+        }))
+    })
+}
+
+/// Works out the verdict from the page the site sends back after an answer is posted.
+pub fn parse_response(html: &str) -> Verdict {
+    let text = article_text(html);
+    if text.contains("That's the right answer") {
+        Verdict::Correct
+    } else if text.contains("That's not the right answer") {
+        if text.contains("your answer is too high") {
+            Verdict::TooHigh
+        } else if text.contains("your answer is too low") {
+            Verdict::TooLow
+        } else {
+            Verdict::Incorrect
+        }
+    } else if text.contains("You gave an answer too recently") {
+        Verdict::RateLimited(wait_time(&text))
+    } else if text.contains("You don't seem to be solving the right level") {
+        Verdict::WrongLevel
+    } else {
+        Verdict::Unknown(text)
+    }
+}
+
+/// The verdicts already given, parsed from a file with one per line in the form
+/// `<year> <day>:<part> <verdict> <answer>`.
+#[derive(Debug, Default)]
+pub struct Guesses(HashMap<(i32, usize, usize), Vec<(String, Verdict)>>);
+
+impl Guesses {
+    /// Loads guesses from a file; a missing file just means nothing has been guessed yet.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(s) => Self::from_str(&s).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Guesses::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Writes the guesses back to a file.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    /// Remembers the site's verdict on an answer, if it's about the answer.
+    pub fn record(&mut self, (year, day, part): (i32, usize, usize), answer: &str, verdict: &Verdict) {
+        if verdict.is_about_answer() {
+            self.0.entry((year, day, part)).or_default().push((answer.to_string(), verdict.clone()));
+        }
+    }
+
+    /// Why `answer` shouldn't be sent, if there's a reason: it's already been tried, the part is already solved, or
+    /// an earlier guess shows it's too high or too low.
+    pub fn reason_not_to_send(&self, key: (i32, usize, usize), answer: &str) -> Option<String> {
+        let guesses = self.0.get(&key)?;
+        if let Some((_, verdict)) = guesses.iter().find(|(guess, _)| guess == answer) {
+            return Some(format!("{answer} has already been tried, and was {verdict}"));
+        }
+        if let Some((correct, _)) = guesses.iter().find(|(_, verdict)| *verdict == Verdict::Correct) {
+            return Some(format!("already solved, with {correct}"));
+        }
+
+        let answer_n = i64::from_str(answer).ok()?;
+        guesses.iter().find_map(|(guess, verdict)| {
+            let guess_n = i64::from_str(guess).ok()?;
+            let direction = match verdict {
+                Verdict::TooHigh if answer_n >= guess_n => "high",
+                Verdict::TooLow if answer_n <= guess_n => "low",
+                _ => return None,
+            };
+            Some(format!("{answer} can't be right, {guess} was too {direction}"))
+        })
+    }
+}
+
+impl FromStr for Guesses {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut guesses = Guesses::default();
+        for (n, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = || format!("line {}: expected `<year> <day>:<part> <verdict> <answer>`, got `{line}`", n + 1);
+            let mut fields = line.splitn(4, ' ');
+            let year = fields.next().and_then(|s| i32::from_str(s).ok()).ok_or_else(invalid)?;
+            let (day, part) = fields.next().and_then(|s| s.split_once(':')).ok_or_else(invalid)?;
+            let day = usize::from_str(day).map_err(|_| invalid())?;
+            let part = usize::from_str(part).map_err(|_| invalid())?;
+            let verdict = fields.next().and_then(|s| Verdict::from_str(s).ok()).ok_or_else(invalid)?;
+            let answer = fields.next().map(str::trim).filter(|s| !s.is_empty()).ok_or_else(invalid)?;
+            guesses.record((year, day, part), answer, &verdict);
+        }
+        Ok(guesses)
+    }
+}
+
+impl Display for Guesses {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "# <year> <day>:<part> <verdict> <answer>")?;
+        let mut keys = self.0.keys().collect::<Vec<_>>();
+        keys.sort();
+        for &(year, day, part) in keys {
+            for (answer, verdict) in &self.0[&(year, day, part)] {
+                writeln!(f, "{year} {day}:{part} {verdict} {answer}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Why an answer wasn't submitted.
+#[derive(Debug)]
+pub enum SubmitError {
+    /// The guesses show it's wrong (or not needed), so it wasn't sent.
+    NotSent(String),
+    /// The request failed.
+    Http(reqwest::Error),
+}
+
+impl Display for SubmitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubmitError::NotSent(reason) => write!(f, "not submitted: {reason}"),
+            SubmitError::Http(e) => write!(f, "could not submit: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SubmitError {}
+
+/// Posts answers to the site (or anything that answers like it).
+#[derive(Debug)]
+pub struct Client {
+    server: String,
+    session: String,
+    http: reqwest::blocking::Client,
+}
+
+impl Client {
+    /// A client for `server` (e.g. [`DEFAULT_SERVER`]), logged in with the `session` cookie.
+    pub fn new(server: &str, session: &str) -> Self {
+        Client {
+            server: server.trim_end_matches('/').to_string(),
+            session: session.to_string(),
+            http: reqwest::blocking::Client::new(),
+        }
+    }
+
+    /// Submits `answer` for `part` of `day` of `year`, unless `guesses` shows there's no point, and remembers the
+    /// verdict in `guesses`.
+    pub fn submit(
+        &self,
+        guesses: &mut Guesses,
+        (year, day, part): (i32, usize, usize),
+        answer: &str,
+    ) -> Result<Verdict, SubmitError> {
+        if let Some(reason) = guesses.reason_not_to_send((year, day, part), answer) {
+            return Err(SubmitError::NotSent(reason));
+        }
+
+        let response = self.http
+            .post(format!("{}/{year}/day/{day}/answer", self.server))
+            .header("Cookie", format!("session={}", self.session))
+            .header("User-Agent", "aoc23 (answer submission)")
+            .form(&[("level", part.to_string()), ("answer", answer.to_string())])
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.text())
+            .map_err(SubmitError::Http)?;
+
+        let verdict = parse_response(&response);
+        guesses.record((year, day, part), answer, &verdict);
+        Ok(verdict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc::{self, Receiver};
+    use std::thread;
+    use super::*;
+
+    // pages like the ones the site sends back, cut down to the part that matters
+    fn page(message: &str) -> String {
+        format!("<!DOCTYPE html><html><body><main>\n<article><p>{message}</p></article>\n</main></body></html>")
+    }
+
+    const RIGHT: &str = "That's the right answer! You are <em>one gold star</em> closer to restoring snow operations.";
+    const TOO_HIGH: &str = "That's not the right answer; your answer is too high. If you're stuck, make sure you're \
+        using the full input data. Please wait one minute before trying again. \
+        <a href=\"/2023/day/1\">[Return to Day 1]</a>";
+    const TOO_RECENT: &str = "You gave an answer too recently; you have to wait after submitting an answer before \
+        trying again.  You have 1m 5s left to wait. <a href=\"/2023/day/1\">[Return to Day 1]</a>";
+
+    // a server that answers each request with the next of `pages`, and passes on each request it gets
+    fn mock_server(pages: Vec<String>) -> (String, Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for page in pages {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                    request += &line;
+                    if line == "\r\n" {
+                        break;
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                request += &String::from_utf8(body).unwrap();
+                tx.send(request).unwrap();
+
+                let headers = format!("Content-Type: text/html\r\nContent-Length: {}\r\nConnection: close", page.len());
+                write!(stream, "HTTP/1.1 200 OK\r\n{headers}\r\n\r\n{page}").unwrap();
+            }
+        });
+        (url, rx)
+    }
+
+    #[test]
+    fn responses() {
+        assert_eq!(parse_response(&page(RIGHT)), Verdict::Correct);
+        assert_eq!(parse_response(&page(TOO_HIGH)), Verdict::TooHigh);
+        assert_eq!(parse_response(&page(&TOO_HIGH.replace("too high", "too low"))), Verdict::TooLow);
+        assert_eq!(parse_response(&page("That's not the right answer. If you're stuck...")), Verdict::Incorrect);
+        assert_eq!(parse_response(&page(TOO_RECENT)), Verdict::RateLimited(Some(Duration::from_secs(65))));
+        assert_eq!(
+            parse_response(&page("You don't seem to be solving the right level.  Did you already complete it?")),
+            Verdict::WrongLevel,
+        );
+        assert_eq!(parse_response(&page("Something <b>new</b>")), Verdict::Unknown("Something new".to_string()));
+    }
+
+    #[test]
+    fn submits_to_the_server_and_remembers_the_verdict() {
+        let (url, requests) = mock_server(vec![page(TOO_HIGH), page(TOO_RECENT), page(RIGHT)]);
+        let client = Client::new(&url, "abc123");
+        let mut guesses = Guesses::default();
+        let key = (2023, 1, 2);
+
+        assert_eq!(client.submit(&mut guesses, key, "54300").unwrap(), Verdict::TooHigh);
+        let request = requests.recv().unwrap();
+        assert!(request.starts_with("POST /2023/day/1/answer HTTP/1.1\r\n"), "{request}");
+        assert!(request.contains("cookie: session=abc123\r\n"), "{request}");
+        assert!(request.ends_with("level=2&answer=54300"), "{request}");
+
+        // neither the same answer nor a higher one is sent again
+        assert!(matches!(client.submit(&mut guesses, key, "54300"), Err(SubmitError::NotSent(_))));
+        assert!(matches!(client.submit(&mut guesses, key, "60000"), Err(SubmitError::NotSent(_))));
+
+        // being rate limited says nothing about the answer, so it can be sent again
+        assert!(matches!(client.submit(&mut guesses, key, "54208").unwrap(), Verdict::RateLimited(_)));
+        assert_eq!(client.submit(&mut guesses, key, "54208").unwrap(), Verdict::Correct);
+        assert_eq!(requests.iter().count(), 2);
+
+        let saved = guesses.to_string();
+        assert_eq!(saved.lines().skip(1).collect::<Vec<_>>(), ["2023 1:2 too-high 54300", "2023 1:2 correct 54208"]);
+        let reloaded = Guesses::from_str(&saved).unwrap();
+        assert_eq!(reloaded.reason_not_to_send(key, "1"), Some("already solved, with 54208".to_string()));
+    }
+}
@@ -0,0 +1,35 @@
+use std::panic::{self, AssertUnwindSafe};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use crate::solution::{Answer, SolveResult};
+
+// runs a part on an example and checks its answer; examples that need different parameters from the real puzzle
+// (a smaller step count, a different test area, ...) pass a closure that calls the parameterised solver instead
+pub(crate) fn check(solver: impl FnOnce(String) -> SolveResult, input: &str, expected: impl Into<Answer>) {
+    let solution = solver(input.to_string()).unwrap_or_else(|e| panic!("example doesn't parse: {e}"));
+    assert_eq!(solution.answer, expected.into());
+}
+
+// the answer of a part that's expected to parse its (generated) input
+pub(crate) fn answer(solver: impl FnOnce(String) -> SolveResult, input: String) -> Answer {
+    solver(input).unwrap_or_else(|e| panic!("generated input doesn't parse: {e}")).answer
+}
+
+// runs `property` on `cases` random cases, each with its own seeded generator so a failure can be replayed alone by
+// setting AOC23_SEED to the seed it reports
+pub(crate) fn property(name: &str, cases: u64, mut property: impl FnMut(&mut StdRng)) {
+    let seeds = match std::env::var("AOC23_SEED") {
+        Ok(seed) => {
+            let seed = seed.parse().expect("AOC23_SEED should be a number");
+            seed..seed + 1
+        }
+        Err(_) => 0..cases,
+    };
+
+    for seed in seeds {
+        let mut rng = StdRng::seed_from_u64(seed);
+        if panic::catch_unwind(AssertUnwindSafe(|| property(&mut rng))).is_err() {
+            panic!("property `{name}` doesn't hold for seed {seed} (rerun with AOC23_SEED={seed})");
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! Checking every part against recorded answers.
+
+use std::fmt::{Display, Formatter};
+use std::panic;
+use crate::answers::Answers;
+use crate::inputs::{self, InputKind};
+use crate::registry::Day;
+use crate::runner::run_catching;
+use crate::solution::{Answer, Solver};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Status {
+    Pass,
+    Fail,
+    Missing,
+    Error,
+    Unimplemented,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Status::Pass => "pass",
+            Status::Fail => "FAIL",
+            Status::Missing => "missing",
+            Status::Error => "ERROR",
+            Status::Unimplemented => "skip",
+        })
+    }
+}
+
+struct Row {
+    day: usize,
+    part: usize,
+    kind: InputKind,
+    status: Status,
+    expected: String,
+    actual: String,
+}
+
+fn check(year: i32, day: usize, part: usize, kind: InputKind, solver: Solver, answers: &Answers) -> Row {
+    let expected = answers.get(year, day, part, kind);
+    let mut row = Row {
+        day,
+        part,
+        kind,
+        status: Status::Missing,
+        expected: expected.unwrap_or("-").to_string(),
+        actual: "-".to_string(),
+    };
+
+    let Some(input) = inputs::read_local(year, day, part, kind) else {
+        row.actual = "(no input)".to_string();
+        return row;
+    };
+
+    match run_catching(solver, input) {
+        Ok(solution) => {
+            row.actual = solution.answer.to_string();
+            row.status = match (&solution.answer, expected) {
+                (Answer::Unimplemented(_), _) => Status::Unimplemented,
+                (_, None) => Status::Missing,
+                (answer, Some(expected)) if answer.to_string() == expected => Status::Pass,
+                _ => Status::Fail,
+            };
+        }
+        Err(failure) => {
+            row.actual = failure.to_string();
+            row.status = Status::Error;
+        }
+    }
+
+    row
+}
+
+/// Runs every part of `days` against the local inputs and prints a pass/fail table; returns false if any part
+/// produced a wrong answer or crashed.
+pub fn verify(days: &[&Day], answers: &Answers) -> bool {
+    // the table reports panics itself, so keep the default hook from interleaving them with the output
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    let mut rows = vec![];
+    for day in days {
+        for (p, &solver) in day.parts.iter().enumerate() {
+            for kind in InputKind::ALL {
+                rows.push(check(day.year, day.day, p + 1, kind, solver, answers));
+            }
+        }
+    }
+
+    panic::set_hook(hook);
+
+    let w_expected = rows.iter().map(|r| r.expected.len()).max().unwrap_or(0).max("expected".len());
+    println!("{:>3}  {:>4}  {:<7}  {:<7}  {:<w_expected$}  actual", "day", "part", "input", "status", "expected");
+    for row in &rows {
+        println!(
+            "{:>3}  {:>4}  {:<7}  {:<7}  {:<w_expected$}  {}",
+            row.day, row.part, row.kind.to_string(), row.status.to_string(), row.expected, row.actual,
+        );
+    }
+
+    let count = |status| rows.iter().filter(|r| r.status == status).count();
+    println!();
+    println!(
+        "{} passed, {} failed, {} errors, {} missing, {} skipped",
+        count(Status::Pass), count(Status::Fail), count(Status::Error), count(Status::Missing), count(Status::Unimplemented),
+    );
+
+    count(Status::Fail) == 0 && count(Status::Error) == 0
+}
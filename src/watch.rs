@@ -0,0 +1,176 @@
+//! Re-running a part whenever its input changes, for iterating on a day.
+//!
+//! Files are watched by polling their modification time and size, which needs nothing from the OS beyond `stat`
+//! and works the same everywhere. The running binary is watched too: rebuilding it (after changing a day's source)
+//! restarts the watch with the new code, by replacing the process (on Unix; elsewhere the old build carries on).
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use crate::registry::Day;
+use crate::runner::{run_catching, run_with_timeout};
+use crate::solution::SolveResult;
+
+/// How often files are checked for changes.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(300);
+
+/// A file being watched for changes.
+#[derive(Debug)]
+pub struct Watched {
+    path: PathBuf,
+    stamp: Option<(SystemTime, u64)>,
+}
+
+impl Watched {
+    /// Starts watching `path`, which doesn't have to exist yet. The first [`Watched::poll`] counts as a change if
+    /// it does.
+    pub fn new(path: PathBuf) -> Self {
+        Watched { path, stamp: None }
+    }
+
+    /// The file being watched.
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Whether the file has appeared or changed since the last poll.
+    pub fn poll(&mut self) -> bool {
+        let stamp = fs::metadata(&self.path).ok().and_then(|m| Some((m.modified().ok()?, m.len())));
+        let changed = stamp.is_some() && stamp != self.stamp;
+        self.stamp = stamp;
+        changed
+    }
+}
+
+// replaces this process with the rebuilt binary, run with the same arguments, so there's no parent left waiting on
+// it; only returns if that fails
+#[cfg(unix)]
+fn restart(exe: &Path) -> io::Error {
+    use std::os::unix::process::CommandExt;
+    Command::new(exe).args(std::env::args_os().skip(1)).exec()
+}
+
+#[cfg(not(unix))]
+fn restart(_exe: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, "restarting in place needs Unix")
+}
+
+/// How an answer compares with the previous run's.
+pub fn describe_change(previous: Option<&str>, current: &str) -> String {
+    match previous {
+        None => current.to_string(),
+        Some(previous) if previous == current => format!("{current} (unchanged)"),
+        Some(previous) => format!("{current} (was {previous})"),
+    }
+}
+
+// one of the inputs the part is re-run on, with the answer it gave last time
+struct Input {
+    label: String,
+    file: Watched,
+    answer: Option<String>,
+}
+
+impl Input {
+    fn run(&mut self, solver: impl Fn(String) -> SolveResult + Clone + Send + 'static, timeout: Option<Duration>) {
+        let input = match fs::read_to_string(self.file.path()) {
+            Ok(input) => input,
+            Err(e) => {
+                println!("{}: could not read {}: {e}", self.label, self.file.path().display());
+                return;
+            }
+        };
+        let result = match timeout {
+            Some(timeout) => run_with_timeout(solver, input, timeout),
+            None => run_catching(solver, input),
+        };
+        match result {
+            Ok(solution) => {
+                let answer = solution.answer.to_string();
+                println!("{}: {}", self.label, describe_change(self.answer.as_deref(), &answer));
+                self.answer = Some(answer);
+            }
+            Err(failure) => println!("{}: error: {failure}", self.label),
+        }
+    }
+}
+
+/// Runs `part` of `day` (with `solver`, which is usually just `day.part(part)`) on each of `inputs` (a label and a
+/// path), then again on an input whenever it changes, until the process is interrupted. Restarts the whole command if
+/// the binary is rebuilt.
+pub fn watch(
+    day: &Day,
+    part: usize,
+    solver: impl Fn(String) -> SolveResult + Clone + Send + 'static,
+    inputs: Vec<(String, PathBuf)>,
+    timeout: Option<Duration>,
+) -> ExitCode {
+    let mut inputs = inputs
+        .into_iter()
+        .map(|(label, path)| Input { label, file: Watched::new(path), answer: None })
+        .collect::<Vec<_>>();
+    for input in &inputs {
+        println!("watching {} for day {} part {part} ({})", input.file.path().display(), day.day, input.label);
+    }
+
+    let mut exe = std::env::current_exe().ok().map(Watched::new);
+    if let Some(exe) = &mut exe {
+        exe.poll();
+    }
+    let mut rebuilding = false;
+    loop {
+        for input in &mut inputs {
+            if input.file.poll() {
+                input.run(solver.clone(), timeout);
+            }
+        }
+
+        // the binary is being written while it's built, so wait for it to settle before restarting
+        if let Some(watched) = &mut exe {
+            if watched.poll() {
+                rebuilding = true;
+            } else if rebuilding {
+                println!("aoc23 was rebuilt, restarting");
+                let e = restart(watched.path());
+                println!("could not restart ({e}), carrying on with the old build");
+                exe = None;
+            }
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_are_described_against_the_last_answer() {
+        assert_eq!(describe_change(None, "142"), "142");
+        assert_eq!(describe_change(Some("142"), "142"), "142 (unchanged)");
+        assert_eq!(describe_change(Some("142"), "281"), "281 (was 142)");
+    }
+
+    #[test]
+    fn polling_sees_files_appear_and_change() {
+        let path = std::env::temp_dir().join(format!("aoc23-watch-test-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut watched = Watched::new(path.clone());
+        assert!(!watched.poll());
+
+        fs::write(&path, "1abc2\n").unwrap();
+        assert!(watched.poll());
+        assert!(!watched.poll());
+
+        // a different size is a change even if the modification time is too coarse to tell
+        fs::write(&path, "1abc2\npqr3stu8vwx\n").unwrap();
+        assert!(watched.poll());
+
+        fs::remove_file(&path).unwrap();
+        assert!(!watched.poll());
+    }
+}